@@ -1,6 +1,10 @@
 use crate::cache_entry::{CacheEntry, PlannedAction};
 use crate::config::MergedConfig;
-use crate::util::{create_backup_dir_name, get_backup_dir, is_dir, path_exists};
+use crate::holders::{self, ProcessHolder, ProcessScan};
+use crate::package_managers::common::{delete_dir, prune_dir};
+use crate::package_managers::traits::{CleanMethod, CleanMode, CleanOutcome};
+use crate::package_managers::PackageManagers;
+use crate::util::{create_backup_dir_name, create_unique_dir, get_backup_dir, is_dir, path_exists};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use fs_extra::dir;
@@ -9,7 +13,6 @@ use std::path::{Path, PathBuf};
 
 /// Action executor for cache operations
 pub struct ActionExecutor {
-    #[allow(dead_code)]
     config: MergedConfig,
 }
 
@@ -19,12 +22,61 @@ impl ActionExecutor {
         Self { config }
     }
 
+    /// Skip entries a running process works in or has files open in.
+    /// With `--kill-holders` those processes are stopped instead (only reported in a dry run).
+    pub fn protect_in_use(&self, entries: &mut [CacheEntry]) -> Vec<HeldEntry> {
+        let acting = |e: &CacheEntry| {
+            matches!(
+                e.planned_action,
+                Some(PlannedAction::Delete | PlannedAction::Backup | PlannedAction::Prune)
+            )
+        };
+        if !entries.iter().any(acting) {
+            return Vec::new();
+        }
+
+        let scan = ProcessScan::new();
+        let mut held = Vec::new();
+        for entry in entries.iter_mut().filter(|e| acting(e)) {
+            let holders = scan.holders_of(&entry.path);
+            if holders.is_empty() {
+                continue;
+            }
+            let mut reason = holders::in_use_reason(&holders);
+            // A dry run with --kill-holders plans to stop the holders, so the entry stays
+            let (stopped, skipped) = match (self.config.kill_holders, self.config.dry_run) {
+                (false, _) => (false, true),
+                (true, true) => (false, false),
+                (true, false) => match holders.iter().try_for_each(holders::stop) {
+                    Ok(()) => (true, false),
+                    Err(e) => {
+                        reason = format!("{}; could not stop it: {}", reason, e);
+                        (false, true)
+                    }
+                },
+            };
+            if skipped {
+                entry.planned_action = Some(PlannedAction::Skip);
+                entry.skip_reason = Some(reason);
+                entry.decided_by = Some("safety: in use by a running process".to_string());
+            }
+            held.push(HeldEntry {
+                path: entry.path.clone(),
+                holders,
+                stopped,
+                skipped,
+            });
+        }
+        held
+    }
+
     /// Execute dry run - show what would be done
     pub fn dry_run(&self, entries: &[CacheEntry]) -> Result<DryRunResult> {
         let mut result = DryRunResult {
             to_delete: Vec::new(),
             to_backup: Vec::new(),
             to_skip: Vec::new(),
+            to_prune: Vec::new(),
             total_size: 0,
             total_count: 0,
         };
@@ -39,6 +91,10 @@ impl ActionExecutor {
                     result.to_backup.push(entry.clone());
                     result.total_size += entry.size_bytes;
                 }
+                Some(PlannedAction::Prune) => {
+                    result.to_prune.push(entry.clone());
+                    result.total_size += entry.freed_bytes();
+                }
                 Some(PlannedAction::Skip) => {
                     result.to_skip.push(entry.clone());
                 }
@@ -54,11 +110,8 @@ impl ActionExecutor {
 
     /// Execute safe delete - move to backup
     pub fn safe_delete(&self, entries: &[CacheEntry]) -> Result<SafeDeleteResult> {
-        let backup_dir = get_backup_dir();
-        let timestamped_backup = backup_dir.join(create_backup_dir_name());
-
-        // Create backup directory
-        std::fs::create_dir_all(&timestamped_backup)
+        // Two runs in the same second must not share a backup directory
+        let timestamped_backup = create_unique_dir(&get_backup_dir(), &create_backup_dir_name())
             .context("Failed to create backup directory")?;
 
         let mut result = SafeDeleteResult {
@@ -101,7 +154,14 @@ impl ActionExecutor {
             ));
         }
 
-        let backup_path = backup_dir.join(source.file_name().unwrap_or_else(|| source.as_os_str()));
+        // Several projects can each contribute a `node_modules`; keep them apart
+        let name = source.file_name().unwrap_or(source.as_os_str());
+        let mut backup_path = backup_dir.join(name);
+        let mut n = 2;
+        while path_exists(&backup_path) {
+            backup_path = backup_dir.join(format!("{}-{}", name.to_string_lossy(), n));
+            n += 1;
+        }
 
         if is_dir(source) {
             // Move directory
@@ -122,14 +182,16 @@ impl ActionExecutor {
             deleted: Vec::new(),
             failed: Vec::new(),
             total_size: 0,
+            native_commands: Vec::new(),
         };
 
         for entry in entries {
             if let Some(PlannedAction::Delete) = entry.planned_action {
-                match self.delete_path(&entry.path) {
-                    Ok(()) => {
+                match self.clean_entry(entry, CleanMode::Delete) {
+                    Ok(outcome) => {
                         result.deleted.push(entry.path.clone());
                         result.total_size += entry.size_bytes;
+                        record_native(&mut result.native_commands, outcome);
                     }
                     Err(e) => {
                         result.failed.push(FailedEntry {
@@ -144,50 +206,107 @@ impl ActionExecutor {
         Ok(result)
     }
 
-    /// Delete a path (file or directory)
-    fn delete_path(&self, path: &Path) -> Result<()> {
-        if !path_exists(path) {
-            return Ok(()); // Already deleted
+    /// Clean one entry, through its package manager when `--js-pm` owns it
+    fn clean_entry(&self, entry: &CacheEntry, mode: CleanMode) -> Result<CleanOutcome> {
+        if self.config.js_pm {
+            if let Some(outcome) = PackageManagers::new(self.config.clone()).clean(entry, mode) {
+                return outcome;
+            }
         }
+        let freed = match mode {
+            CleanMode::Delete => {
+                delete_dir(&entry.path)?;
+                entry.size_bytes
+            }
+            CleanMode::Prune => prune_dir(&entry.path, self.config.stale_days)?,
+        };
+        Ok(CleanOutcome {
+            path: entry.path.clone(),
+            freed,
+            method: CleanMethod::Filesystem,
+        })
+    }
 
-        if is_dir(path) {
-            std::fs::remove_dir_all(path).context("Failed to remove directory")?;
-        } else {
-            std::fs::remove_file(path).context("Failed to remove file")?;
+    /// Execute prune - trim stale content inside caches that support it
+    pub fn prune(&self, entries: &[CacheEntry]) -> Result<PruneResult> {
+        let mut result = PruneResult {
+            pruned: Vec::new(),
+            failed: Vec::new(),
+            total_size: 0,
+            native_commands: Vec::new(),
+        };
+
+        for entry in entries {
+            if let Some(PlannedAction::Prune) = entry.planned_action {
+                match self.clean_entry(entry, CleanMode::Prune) {
+                    Ok(outcome) => {
+                        let freed = outcome.freed;
+                        result.pruned.push(PrunedEntry {
+                            path: entry.path.clone(),
+                            freed,
+                        });
+                        result.total_size += freed;
+                        record_native(&mut result.native_commands, outcome);
+                    }
+                    Err(e) => {
+                        result.failed.push(FailedEntry {
+                            path: entry.path.clone(),
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            }
         }
 
-        Ok(())
+        Ok(result)
     }
 
     /// Restore from last backup
     pub fn restore_last_backup(&self) -> Result<RestoreResult> {
+        let backups = self.list_backups()?;
+        let latest = backups
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No backup directories found"))?;
+        self.restore_from_backup(&latest.path)
+    }
+
+    /// Restore the backup directory called `name` (as listed by `backups`)
+    pub fn restore_backup(&self, name: &str) -> Result<RestoreResult> {
+        let backup = self
+            .list_backups()?
+            .into_iter()
+            .find(|backup| backup.name == name)
+            .ok_or_else(|| anyhow::anyhow!("No backup named {}", name))?;
+        self.restore_from_backup(&backup.path)
+    }
+
+    /// Backups made by safe delete, newest first
+    pub fn list_backups(&self) -> Result<Vec<BackupInfo>> {
         let backup_dir = get_backup_dir();
 
         if !path_exists(&backup_dir) {
             return Err(anyhow::anyhow!("No backup directory found"));
         }
 
-        // Find the most recent backup
-        let mut backup_dirs: Vec<_> = std::fs::read_dir(&backup_dir)
-            .context("Failed to read backup directory")?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.path().is_dir())
-            .collect();
-
-        backup_dirs.sort_by(|a, b| {
-            b.metadata()
-                .unwrap()
-                .modified()
-                .unwrap()
-                .cmp(&a.metadata().unwrap().modified().unwrap())
-        });
-
-        if backup_dirs.is_empty() {
-            return Err(anyhow::anyhow!("No backup directories found"));
+        let mut backups = Vec::new();
+        for entry in std::fs::read_dir(&backup_dir).context("Failed to read backup directory")? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            backups.push(BackupInfo {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                created: crate::util::get_mtime(&path)?,
+                entries: std::fs::read_dir(&path)?.count(),
+                size_bytes: crate::util::get_size(&path).unwrap_or(0),
+                path,
+            });
         }
-
-        let latest_backup = backup_dirs[0].path();
-        self.restore_from_backup(&latest_backup)
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created));
+        Ok(backups)
     }
 
     /// Restore from a specific backup directory
@@ -254,7 +373,6 @@ impl ActionExecutor {
     }
 
     /// Clean old backups (older than specified days)
-    #[allow(dead_code)]
     pub fn clean_old_backups(&self, days: u32) -> Result<CleanupResult> {
         let backup_dir = get_backup_dir();
 
@@ -299,6 +417,7 @@ pub struct DryRunResult {
     pub to_delete: Vec<CacheEntry>,
     pub to_backup: Vec<CacheEntry>,
     pub to_skip: Vec<CacheEntry>,
+    pub to_prune: Vec<CacheEntry>,
     pub total_size: u64,
     pub total_count: usize,
 }
@@ -327,6 +446,17 @@ pub struct BackupEntry {
     pub size: u64,
 }
 
+/// A cache a running process was using when the cleanup started
+#[derive(Debug, Clone, Serialize)]
+pub struct HeldEntry {
+    pub path: PathBuf,
+    pub holders: Vec<ProcessHolder>,
+    /// The holders were stopped by `--kill-holders`
+    pub stopped: bool,
+    /// The entry is skipped instead of cleaned
+    pub skipped: bool,
+}
+
 /// Failed entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FailedEntry {
@@ -340,6 +470,34 @@ pub struct HardDeleteResult {
     pub deleted: Vec<PathBuf>,
     pub failed: Vec<FailedEntry>,
     pub total_size: u64,
+    /// Package manager commands cleaning was delegated to
+    pub native_commands: Vec<String>,
+}
+
+/// Prune result
+#[derive(Debug, Clone)]
+pub struct PruneResult {
+    pub pruned: Vec<PrunedEntry>,
+    pub failed: Vec<FailedEntry>,
+    pub total_size: u64,
+    /// Package manager commands pruning was delegated to
+    pub native_commands: Vec<String>,
+}
+
+/// Remember a delegated command once, however many entries it covered
+fn record_native(commands: &mut Vec<String>, outcome: CleanOutcome) {
+    if let CleanMethod::Native(command) = outcome.method {
+        if !commands.contains(&command) {
+            commands.push(command);
+        }
+    }
+}
+
+/// Pruned entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrunedEntry {
+    pub path: PathBuf,
+    pub freed: u64,
 }
 
 /// Restore result
@@ -350,9 +508,25 @@ pub struct RestoreResult {
     pub backup_dir: PathBuf,
 }
 
+/// A timestamped backup directory made by safe delete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub path: PathBuf,
+    pub created: DateTime<Utc>,
+    /// Caches moved into this backup
+    pub entries: usize,
+    pub size_bytes: u64,
+}
+
+impl BackupInfo {
+    pub fn size_human(&self) -> String {
+        humansize::format_size(self.size_bytes, humansize::DECIMAL)
+    }
+}
+
 /// Cleanup result
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CleanupResult {
     pub removed: Vec<PathBuf>,
     pub total_freed: u64,
@@ -368,7 +542,6 @@ mod tests {
 
     fn create_test_config() -> MergedConfig {
         MergedConfig {
-            list: false,
             dry_run: false,
             force: false,
             json: false,
@@ -380,13 +553,23 @@ mod tests {
             backup_dir: ".cachekill-backup".to_string(),
             docker: false,
             npx: false,
-            restore_last: false,
             all: false,
             js_pm: false,
+            npx_packages: vec![],
+            keep_versions: None,
+            gitignored: false,
+            min_size: crate::config::DEFAULT_MIN_SIZE,
+            rules: vec![],
+            explain: false,
+            free_target: None,
+            max_total_cache: None,
+            rehydrate: None,
+            kill_holders: false,
         }
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_action_executor_creation() {
         let config = create_test_config();
         let _executor = ActionExecutor::new(config);
@@ -423,6 +606,26 @@ mod tests {
         assert_eq!(result.total_size, 3000);
     }
 
+    #[test]
+    fn test_dry_run_counts_reclaimable_bytes_for_prune() {
+        let config = create_test_config();
+        let executor = ActionExecutor::new(config);
+
+        let entries = vec![CacheEntry::new(
+            PathBuf::from("_cacache"),
+            CacheKind::JavaScript,
+            5000,
+            Utc::now(),
+            true,
+        )
+        .with_planned_action(PlannedAction::Prune)
+        .with_reclaimable_bytes(1200)];
+
+        let result = executor.dry_run(&entries).unwrap();
+        assert_eq!(result.to_prune.len(), 1);
+        assert_eq!(result.total_size, 1200);
+    }
+
     #[test]
     fn test_safe_delete() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::git;
+use crate::util::get_mtime;
+
+/// Lockfiles whose changes mean dependencies were touched
+const LOCKFILES: &[&str] = &[
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "bun.lock",
+    "deno.lock",
+    "Cargo.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "pdm.lock",
+    "gradle.lockfile",
+    "composer.lock",
+    "Gemfile.lock",
+    "go.sum",
+];
+
+/// How deep the fallback walk looks for source files outside git
+const MAX_WALK_DEPTH: usize = 8;
+
+/// The signal that decided when a project was last active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivitySignal {
+    #[serde(rename = "git-commit")]
+    GitCommit,
+    #[serde(rename = "git-reflog")]
+    GitReflog,
+    /// Newest mtime among tracked files
+    #[serde(rename = "source-file")]
+    SourceFile,
+    #[serde(rename = "lockfile")]
+    Lockfile,
+    /// Newest mtime outside the caches, for projects not under git
+    #[serde(rename = "file-tree")]
+    FileTree,
+}
+
+impl std::fmt::Display for ActivitySignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivitySignal::GitCommit => write!(f, "git commit"),
+            ActivitySignal::GitReflog => write!(f, "git reflog"),
+            ActivitySignal::SourceFile => write!(f, "source file"),
+            ActivitySignal::Lockfile => write!(f, "lockfile"),
+            ActivitySignal::FileTree => write!(f, "file tree"),
+        }
+    }
+}
+
+/// When a project was last worked on, judged from its sources rather than its caches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectActivity {
+    pub last_active: DateTime<Utc>,
+    /// The signal that produced `last_active`
+    pub signal: ActivitySignal,
+}
+
+impl ProjectActivity {
+    /// Combine the project's signals: last commit, reflog, tracked source file
+    /// mtimes and lockfile changes. Outside git, the newest file outside
+    /// `caches` is used instead.
+    pub fn detect(project_root: &Path, caches: &[&Path]) -> Option<Self> {
+        let mut signals: Vec<(DateTime<Utc>, ActivitySignal)> = Vec::new();
+
+        // Pushed from weakest to strongest: on a tie the later signal is reported
+        match git::tracked_files(project_root) {
+            Some(files) => signals.extend(
+                files
+                    .iter()
+                    .filter(|file| !caches.iter().any(|cache| file.starts_with(cache)))
+                    .filter_map(|file| get_mtime(file).ok())
+                    .max()
+                    .map(|t| (t, ActivitySignal::SourceFile)),
+            ),
+            None => signals
+                .extend(newest_file(project_root, caches).map(|t| (t, ActivitySignal::FileTree))),
+        }
+
+        signals.extend(
+            LOCKFILES
+                .iter()
+                .filter_map(|name| get_mtime(&project_root.join(name)).ok())
+                .max()
+                .map(|t| (t, ActivitySignal::Lockfile)),
+        );
+
+        signals.extend(git::last_commit_time(project_root).map(|t| (t, ActivitySignal::GitCommit)));
+        signals.extend(git::last_reflog_time(project_root).map(|t| (t, ActivitySignal::GitReflog)));
+
+        signals
+            .into_iter()
+            .max_by_key(|(time, _)| *time)
+            .map(|(last_active, signal)| Self {
+                last_active,
+                signal,
+            })
+    }
+
+    /// Whole days since the project was last active
+    pub fn inactive_days(&self) -> i64 {
+        (Utc::now() - self.last_active).num_days()
+    }
+}
+
+/// Newest file modification under `root`, skipping caches and hidden directories
+fn newest_file(root: &Path, caches: &[&Path]) -> Option<DateTime<Utc>> {
+    walkdir::WalkDir::new(root)
+        .max_depth(MAX_WALK_DEPTH)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !(e.file_name().to_string_lossy().starts_with('.') || caches.contains(&e.path()))
+        })
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| get_mtime(e.path()).ok())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn set_mtime(path: &Path, days_ago: i64) {
+        let time =
+            std::time::SystemTime::now() - std::time::Duration::from_secs(days_ago as u64 * 86_400);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_fresh_cache_does_not_make_project_active() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("index.js"), "").unwrap();
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::write(root.join("node_modules/fresh.js"), "").unwrap();
+        set_mtime(&root.join("index.js"), 90);
+        set_mtime(&root.join("package.json"), 120);
+
+        let caches = [root.join("node_modules")];
+        let caches: Vec<&Path> = caches.iter().map(|p| p.as_path()).collect();
+        let activity = ProjectActivity::detect(root, &caches).unwrap();
+        assert_eq!(activity.signal, ActivitySignal::FileTree);
+        assert_eq!(activity.inactive_days(), 90);
+
+        // A lockfile change counts as activity
+        fs::write(root.join("package-lock.json"), "{}").unwrap();
+        set_mtime(&root.join("package-lock.json"), 10);
+        let activity = ProjectActivity::detect(root, &caches).unwrap();
+        assert_eq!(activity.signal, ActivitySignal::Lockfile);
+        assert_eq!(activity.inactive_days(), 10);
+    }
+
+    #[test]
+    fn test_git_signals() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(root)
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .env("GIT_COMMITTER_DATE", "2020-01-01T00:00:00Z")
+                .env("GIT_AUTHOR_DATE", "2020-01-01T00:00:00Z")
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false)
+        };
+        if !git(&["init", "-q"]) {
+            return;
+        }
+        fs::write(root.join("main.py"), "").unwrap();
+        set_mtime(&root.join("main.py"), 400);
+        assert!(git(&["add", "main.py"]));
+        assert!(git(&["commit", "-q", "-m", "init"]));
+
+        assert_eq!(
+            git::last_commit_time(root).unwrap().to_rfc3339(),
+            "2020-01-01T00:00:00+00:00"
+        );
+        // Switching branches is recorded in the reflog with the current time
+        assert!(Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["checkout", "-q", "-b", "feature"])
+            .status()
+            .unwrap()
+            .success());
+        let activity = ProjectActivity::detect(root, &[]).unwrap();
+        assert_eq!(activity.signal, ActivitySignal::GitReflog);
+        assert_eq!(activity.inactive_days(), 0);
+    }
+}
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::regen::RegenCost;
+
 /// Represents a cache entry with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry {
@@ -17,6 +19,48 @@ pub struct CacheEntry {
     pub stale: bool,
     /// Planned action for this entry
     pub planned_action: Option<PlannedAction>,
+    /// Bytes a prune would free, for caches that can be trimmed in place
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reclaimable_bytes: Option<u64>,
+    /// Bytes still in use by projects, for caches shared through hard links
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_bytes: Option<u64>,
+    /// Command that rebuilds this cache after removal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regenerate: Option<String>,
+    /// Command `clean --rehydrate` runs instead of `regenerate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rehydrate: Option<String>,
+    /// Estimated cost of getting the cache back after removal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regen_cost: Option<RegenCost>,
+    /// Project directory `regenerate` runs in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<PathBuf>,
+    /// Where the location was configured (env var, config file or default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_source: Option<String>,
+    /// Why the entry is skipped, when it failed verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+    /// How sure we are the path is disposable, for paths inside a git repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    /// When the owning project was last worked on (commits, sources, lockfiles)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_last_active: Option<DateTime<Utc>>,
+    /// Detectors that recognized the owning project (`javascript`, `rust`, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub project_types: Vec<String>,
+    /// Package manager owning the cache, for `--js-pm` entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manager: Option<String>,
+    /// Detector that proposed the cache (`javascript`, `cachedir-tag`, `paths`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detector: Option<String>,
+    /// What decided the planned action (a rule, a safety check or the default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decided_by: Option<String>,
 }
 
 /// Types of caches that can be detected
@@ -55,6 +99,26 @@ impl std::fmt::Display for CacheKind {
     }
 }
 
+/// How sure we are that a cache path holds nothing but generated files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Confidence {
+    /// The repository ignores the path
+    #[serde(rename = "high")]
+    High,
+    /// The path is neither tracked nor ignored
+    #[serde(rename = "medium")]
+    Medium,
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Confidence::High => write!(f, "high"),
+            Confidence::Medium => write!(f, "medium"),
+        }
+    }
+}
+
 /// Planned action for a cache entry
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PlannedAction {
@@ -64,6 +128,9 @@ pub enum PlannedAction {
     Backup,
     #[serde(rename = "skip")]
     Skip,
+    /// Trim stale content in place instead of removing the whole cache
+    #[serde(rename = "prune")]
+    Prune,
 }
 
 impl std::fmt::Display for PlannedAction {
@@ -72,6 +139,7 @@ impl std::fmt::Display for PlannedAction {
             PlannedAction::Delete => write!(f, "delete"),
             PlannedAction::Backup => write!(f, "backup"),
             PlannedAction::Skip => write!(f, "skip"),
+            PlannedAction::Prune => write!(f, "prune"),
         }
     }
 }
@@ -92,6 +160,20 @@ impl CacheEntry {
             last_used,
             stale,
             planned_action: None,
+            reclaimable_bytes: None,
+            shared_bytes: None,
+            regenerate: None,
+            rehydrate: None,
+            regen_cost: None,
+            project_root: None,
+            config_source: None,
+            skip_reason: None,
+            confidence: None,
+            project_last_active: None,
+            project_types: Vec::new(),
+            manager: None,
+            detector: None,
+            decided_by: None,
         }
     }
 
@@ -101,6 +183,98 @@ impl CacheEntry {
         self
     }
 
+    /// Set the number of bytes a prune would free
+    pub fn with_reclaimable_bytes(mut self, bytes: u64) -> Self {
+        self.reclaimable_bytes = Some(bytes);
+        self
+    }
+
+    /// Set the number of bytes still shared with projects
+    pub fn with_shared_bytes(mut self, bytes: u64) -> Self {
+        self.shared_bytes = Some(bytes);
+        self
+    }
+
+    /// Set the command that rebuilds this cache
+    pub fn with_regenerate(mut self, command: Option<String>) -> Self {
+        self.regenerate = command;
+        self
+    }
+
+    /// Set the command `clean --rehydrate` runs instead of `regenerate`
+    pub fn with_rehydrate(mut self, command: Option<String>) -> Self {
+        self.rehydrate = command;
+        self
+    }
+
+    /// Command `clean --rehydrate` runs to bring this cache back
+    pub fn rehydrate_command(&self) -> Option<&str> {
+        self.rehydrate.as_deref().or(self.regenerate.as_deref())
+    }
+
+    /// Set the estimated cost of getting this cache back
+    pub fn with_regen_cost(mut self, cost: RegenCost) -> Self {
+        self.regen_cost = Some(cost);
+        self
+    }
+
+    /// Record where this location was configured
+    pub fn with_config_source(mut self, source: impl Into<String>) -> Self {
+        self.config_source = Some(source.into());
+        self
+    }
+
+    /// Record why this entry must be skipped
+    pub fn with_skip_reason(mut self, reason: Option<String>) -> Self {
+        self.skip_reason = reason;
+        self
+    }
+
+    /// Set how sure we are the path is disposable
+    pub fn with_confidence(mut self, confidence: Option<Confidence>) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// Record when the owning project was last active
+    pub fn with_project_last_active(mut self, last_active: Option<DateTime<Utc>>) -> Self {
+        self.project_last_active = last_active;
+        self
+    }
+
+    /// Record the package manager owning this cache
+    pub fn with_manager(mut self, manager: &str) -> Self {
+        self.manager = Some(manager.to_string());
+        self
+    }
+
+    /// Record the detector that proposed this cache
+    pub fn with_detector(mut self, detector: &str) -> Self {
+        self.detector = Some(detector.to_string());
+        self
+    }
+
+    /// Bytes freed by carrying out the planned action
+    pub fn freed_bytes(&self) -> u64 {
+        match self.planned_action {
+            Some(PlannedAction::Delete) | Some(PlannedAction::Backup) => self.size_bytes,
+            Some(PlannedAction::Prune) => self.reclaimable_bytes.unwrap_or(0),
+            Some(PlannedAction::Skip) | None => 0,
+        }
+    }
+
+    /// Get a human-readable reclaimable size string
+    pub fn reclaimable_human(&self) -> Option<String> {
+        self.reclaimable_bytes
+            .map(|bytes| humansize::format_size(bytes, humansize::DECIMAL))
+    }
+
+    /// Get a human-readable shared size string
+    pub fn shared_human(&self) -> Option<String> {
+        self.shared_bytes
+            .map(|bytes| humansize::format_size(bytes, humansize::DECIMAL))
+    }
+
     /// Get a human-readable size string
     pub fn size_human(&self) -> String {
         humansize::format_size(self.size_bytes, humansize::DECIMAL)
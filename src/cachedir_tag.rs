@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::config::MergedConfig;
+use crate::discover::DiscoveryResult;
+
+/// Standard signature from the Cache Directory Tagging Specification
+pub const SIGNATURE: &str = "Signature: 8a477f597d28d172789f06886806bc55";
+
+/// File name of the tag
+pub const TAG_FILE: &str = "CACHEDIR.TAG";
+
+/// How deep below a scan root tagged directories are looked for
+pub const MAX_DEPTH: usize = 6;
+
+const TAG_CONTENT: &str = "Signature: 8a477f597d28d172789f06886806bc55
+# This file is a cache directory tag created by cachekill.
+# For information about cache directory tags, see:
+#\thttps://bford.info/cachedir/
+";
+
+/// Check whether `dir` holds a `CACHEDIR.TAG` that starts with the signature
+pub fn is_tagged(dir: &Path) -> bool {
+    let Ok(mut file) = fs::File::open(dir.join(TAG_FILE)) else {
+        return false;
+    };
+    let mut header = [0u8; SIGNATURE.len()];
+    file.read_exact(&mut header).is_ok() && header == SIGNATURE.as_bytes()
+}
+
+/// Write a tag into `dir`. Returns `false` when a valid tag is already there.
+pub fn write_tag(dir: &Path) -> Result<bool> {
+    if is_tagged(dir) {
+        return Ok(false);
+    }
+    fs::write(dir.join(TAG_FILE), TAG_CONTENT)
+        .with_context(|| format!("Failed to write {}", dir.join(TAG_FILE).display()))?;
+    Ok(true)
+}
+
+/// Tagged directories under `root`, without descending into tagged directories,
+/// `.git` or `node_modules`
+pub fn find_tagged(root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut tagged = Vec::new();
+    let mut walker = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .follow_links(false)
+        .into_iter();
+
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let name = entry.file_name();
+        if name == ".git" || name == "node_modules" {
+            walker.skip_current_dir();
+            continue;
+        }
+        if is_tagged(entry.path()) {
+            tagged.push(entry.path().to_path_buf());
+            walker.skip_current_dir();
+        }
+    }
+
+    tagged
+}
+
+#[derive(Debug, Serialize)]
+struct TagReport {
+    mode: String,
+    tagged: Vec<PathBuf>,
+    already_tagged: Vec<PathBuf>,
+    failed: Vec<String>,
+}
+
+/// Write `CACHEDIR.TAG` into every detected cache so backup tools skip it
+pub fn handle_tag_caches(config: &MergedConfig) -> Result<()> {
+    let discovery = DiscoveryResult::discover(config).context("Failed to discover caches")?;
+    let mut report = TagReport {
+        mode: if config.dry_run { "tag-dry-run" } else { "tag" }.to_string(),
+        tagged: Vec::new(),
+        already_tagged: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    // Only caches that would be proposed for removal are tagged
+    let caches = discovery.cache_entries.iter().filter(|cache| {
+        cache.skip_reason.is_none() && cache.git.map_or(true, |git| git.tracked_files == 0)
+    });
+    for cache in caches {
+        if is_tagged(&cache.path) {
+            report.already_tagged.push(cache.path.clone());
+        } else if config.dry_run {
+            report.tagged.push(cache.path.clone());
+        } else {
+            match write_tag(&cache.path) {
+                Ok(_) => report.tagged.push(cache.path.clone()),
+                Err(e) => report.failed.push(format!("{:#}", e)),
+            }
+        }
+    }
+
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        let verb = if config.dry_run {
+            "Would tag"
+        } else {
+            "Tagged"
+        };
+        println!("🏷️  {} {} cache directories", verb, report.tagged.len());
+        for path in &report.tagged {
+            println!("  {}", path.display());
+        }
+        if !report.already_tagged.is_empty() {
+            println!("  ({} already tagged)", report.already_tagged.len());
+        }
+        for failure in &report.failed {
+            eprintln!("  ❌ {}", failure);
+        }
+    }
+
+    if !report.failed.is_empty() {
+        std::process::exit(crate::ci::exit_codes::PARTIAL_SUCCESS);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_and_detect_tag() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        assert!(!is_tagged(dir));
+
+        assert!(write_tag(dir).unwrap());
+        assert!(is_tagged(dir));
+        assert!(!write_tag(dir).unwrap());
+
+        // A tag without the signature does not count
+        fs::write(dir.join(TAG_FILE), "Signature: nope").unwrap();
+        assert!(!is_tagged(dir));
+    }
+
+    #[test]
+    fn test_find_tagged_stops_at_tagged_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in [
+            "a/cache",
+            "a/cache/inner",
+            "b/.git/cache",
+            "c/node_modules/x",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            write_tag(&root.join(dir)).unwrap();
+        }
+        fs::create_dir_all(root.join("d")).unwrap();
+
+        assert_eq!(find_tagged(root, MAX_DEPTH), vec![root.join("a/cache")]);
+    }
+}
@@ -10,7 +10,7 @@ use crate::discover::DiscoveryResult;
 use crate::inspect::CacheInspector;
 
 /// CI mode for non-interactive cache management
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
 pub enum CiMode {
     #[serde(rename = "prebuild")]
     Prebuild,
@@ -63,7 +63,7 @@ impl CiCacheManager {
 
         // Inspect caches
         let inspector = CacheInspector::new(self.config.clone());
-        let entries = inspector
+        let mut entries = inspector
             .inspect_caches(&discovery.cache_entries)
             .context("Failed to inspect caches")?;
 
@@ -95,7 +95,8 @@ impl CiCacheManager {
                     let total_size: u64 = entries.iter().map(|e| e.size_bytes).sum();
                     (total_size, None)
                 } else {
-                    // Actually clean up
+                    // Actually clean up, leaving caches a running process still uses
+                    executor.protect_in_use(&mut entries);
                     let result = executor
                         .safe_delete(&entries)
                         .context("Failed to execute safe delete")?;
@@ -179,9 +180,10 @@ pub mod exit_codes {
     pub const SUCCESS: i32 = 0;
     pub const PARTIAL_SUCCESS: i32 = 2;
     pub const NOTHING_TO_DO: i32 = 3;
-    #[allow(dead_code)]
     pub const CONFIG_ERROR: i32 = 4;
     pub const FATAL_ERROR: i32 = 5;
+    /// Another run holds the lock and `--no-wait` was given
+    pub const LOCKED: i32 = 6;
 }
 
 /// Handle CI mode execution with proper exit codes
@@ -213,6 +215,7 @@ mod tests {
     use crate::config::MergedConfig;
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_ci_result_creation() {
         let mut config = MergedConfig::default();
         config.dry_run = true;
@@ -1,12 +1,41 @@
 use crate::cache_entry::LanguageFilter;
-use crate::util::{expand_home, get_current_dir};
+use crate::ci::exit_codes;
+use crate::freespace::FreeTarget;
+use crate::rules::Rule;
+use crate::util::{expand_home, get_current_dir, parse_size, suggest};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// Configuration loaded from .cachekillrc file
+/// Default `--min-size` for `--gitignored`: 10 MB
+pub const DEFAULT_MIN_SIZE: u64 = 10_000_000;
+
+/// System-wide configuration, the lowest-precedence file
+const SYSTEM_CONFIG: &str = "/etc/cachekill/config.toml";
+
+/// Prefix of environment variables that override configuration files
+const ENV_PREFIX: &str = "CACHEKILL_";
+
+/// Keys that can be set through `CACHEKILL_<KEY>` environment variables
+const ENV_KEYS: &[&str] = &[
+    "default_lang",
+    "stale_days",
+    "safe_delete",
+    "backup_dir",
+    "include_paths",
+    "exclude_paths",
+    "include_docker",
+    "include_npx",
+    "include_js_pm",
+    "max_total_cache",
+    "profile",
+];
+
+/// Configuration layered from config files and `CACHEKILL_*` environment variables
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     /// Default language filter
     pub default_lang: Option<LanguageFilter>,
@@ -24,6 +53,80 @@ pub struct Config {
     pub include_docker: Option<bool>,
     /// Whether to include NPX cache by default
     pub include_npx: Option<bool>,
+    /// Whether to include JS package manager caches by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_js_pm: Option<bool>,
+    /// Budget for all caches together (`50GB`); least recently used caches are evicted past it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_cache: Option<String>,
+    /// Cleanup policies, evaluated by priority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<Rule>>,
+    /// Profile applied when `--profile` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Named presets from `[profiles.<name>]`, selected with `--profile`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<BTreeMap<String, Profile>>,
+    /// Layers each key was set by; keys missing here keep their default
+    #[serde(skip)]
+    pub origins: BTreeMap<String, Vec<String>>,
+}
+
+/// A named bundle of settings from `[profiles.<name>]`, laid over the config files
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_lang: Option<LanguageFilter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_days: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safe_delete: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_paths: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_paths: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_docker: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_npx: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_js_pm: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_cache: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<Rule>>,
+}
+
+impl From<Profile> for Config {
+    fn from(profile: Profile) -> Self {
+        Self {
+            default_lang: profile.default_lang,
+            stale_days: profile.stale_days,
+            safe_delete: profile.safe_delete,
+            backup_dir: None,
+            include_paths: profile.include_paths,
+            exclude_paths: profile.exclude_paths,
+            include_docker: profile.include_docker,
+            include_npx: profile.include_npx,
+            include_js_pm: profile.include_js_pm,
+            max_total_cache: profile.max_total_cache,
+            rules: profile.rules,
+            profile: None,
+            profiles: None,
+            origins: BTreeMap::new(),
+        }
+    }
+}
+
+/// One effective setting, as printed by `config show`
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveValue {
+    pub key: String,
+    pub value: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
 }
 
 impl Default for Config {
@@ -41,14 +144,19 @@ impl Default for Config {
             ]),
             include_docker: Some(false),
             include_npx: Some(false),
+            include_js_pm: None,
+            max_total_cache: None,
+            rules: None,
+            profile: None,
+            profiles: None,
+            origins: BTreeMap::new(),
         }
     }
 }
 
 /// CLI arguments that override config
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct CliArgs {
-    pub list: bool,
     pub dry_run: bool,
     pub force: bool,
     pub json: bool,
@@ -60,15 +168,24 @@ pub struct CliArgs {
     pub backup_dir: Option<String>,
     pub docker: bool,
     pub npx: bool,
-    pub restore_last: bool,
     pub all: bool,
     pub js_pm: bool,
+    pub npx_packages: Option<Vec<String>>,
+    pub keep_versions: Option<usize>,
+    pub gitignored: bool,
+    pub min_size: Option<u64>,
+    pub explain: bool,
+    /// `--free` / `--until-free` target
+    pub free_target: Option<FreeTarget>,
+    /// `--rehydrate`, with the number of commands run at once
+    pub rehydrate: Option<usize>,
+    /// `--kill-holders`
+    pub kill_holders: bool,
 }
 
 /// Merged configuration combining config file and CLI args
 #[derive(Debug, Clone)]
 pub struct MergedConfig {
-    pub list: bool,
     pub dry_run: bool,
     pub force: bool,
     pub json: bool,
@@ -81,68 +198,358 @@ pub struct MergedConfig {
     pub backup_dir: String,
     pub docker: bool,
     pub npx: bool,
-    pub restore_last: bool,
     pub all: bool,
     pub js_pm: bool,
+    /// NPX package names to target (empty means every package)
+    pub npx_packages: Vec<String>,
+    /// Number of most recently used versions to keep per NPX package
+    pub keep_versions: Option<usize>,
+    /// Propose every gitignored directory instead of detector caches
+    pub gitignored: bool,
+    /// Smallest directory `--gitignored` proposes, in bytes
+    pub min_size: u64,
+    /// Cleanup policies from `[[rules]]`
+    pub rules: Vec<Rule>,
+    /// Show what decided each entry's action
+    pub explain: bool,
+    /// `--free` / `--until-free` target
+    pub free_target: Option<FreeTarget>,
+    /// `max_total_cache` in bytes, counting global caches whatever the selection flags
+    pub max_total_cache: Option<u64>,
+    /// Run regenerate commands after cleaning, this many at once
+    pub rehydrate: Option<usize>,
+    /// Stop processes using a cache instead of skipping it
+    pub kill_holders: bool,
 }
 
 impl Config {
-    /// Load configuration from .cachekillrc file
-    pub fn load() -> Result<Self> {
-        let config_path = Self::find_config_file()?;
+    /// Load every configuration layer, later ones taking precedence:
+    /// `/etc/cachekill/config.toml`, `$XDG_CONFIG_HOME/cachekill/config.toml`,
+    /// the nearest `.cachekillrc`, the selected profile, then `CACHEKILL_*`
+    /// environment variables. `profile` (from `--profile`) overrides the
+    /// `profile` key and `CACHEKILL_PROFILE`.
+    pub fn load(profile: Option<&str>) -> Result<Self> {
+        let mut config = Config::default();
+        for (layer, path) in Self::config_files()? {
+            if path.exists() {
+                let origin = format!("{} ({})", layer, path.display());
+                config.overlay(Self::read_file(&path)?, &origin);
+            }
+        }
 
-        if config_path.exists() {
-            let content =
-                fs::read_to_string(&config_path).context("Failed to read .cachekillrc file")?;
+        let selected = profile
+            .map(str::to_string)
+            .or_else(|| std::env::var(format!("{}PROFILE", ENV_PREFIX)).ok())
+            .or_else(|| config.profile.clone());
+        if let Some(name) = selected {
+            config.apply_profile(&name)?;
+        }
+        config.apply_env(std::env::vars())?;
+        if let Some(name) = profile {
+            config.profile = Some(name.to_string());
+            config
+                .origins
+                .insert("profile".to_string(), vec!["cli (--profile)".to_string()]);
+        }
+        Ok(config)
+    }
 
-            let config: Config =
-                toml::from_str(&content).context("Failed to parse .cachekillrc file")?;
+    /// Lay the named profile over the settings loaded so far
+    fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profiles = self.profiles.clone().unwrap_or_default();
+        let Some(profile) = profiles.get(name) else {
+            let names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+            match suggest(name, &names) {
+                Some(candidate) => {
+                    anyhow::bail!("unknown profile `{}` (did you mean `{}`?)", name, candidate)
+                }
+                None if names.is_empty() => {
+                    anyhow::bail!("unknown profile `{}`: no [profiles] are configured", name)
+                }
+                None => anyhow::bail!(
+                    "unknown profile `{}`, expected one of: {}",
+                    name,
+                    names.join(", ")
+                ),
+            }
+        };
+        self.overlay(profile.clone().into(), &format!("profile ({})", name));
+        Ok(())
+    }
 
-            Ok(config)
-        } else {
-            Ok(Config::default())
+    /// Read and strictly parse one configuration file
+    fn read_file(path: &Path) -> Result<Config> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content, path)
+    }
+
+    /// Parse a configuration file, rejecting unknown keys and invalid rules.
+    /// Errors name the file, line and column, with a suggestion for misspelled keys.
+    fn parse(content: &str, path: &Path) -> Result<Config> {
+        let config: Config = toml::from_str(content).map_err(|e| {
+            let (line, column) = e
+                .span()
+                .map(|span| line_column(content, span.start))
+                .unwrap_or((1, 1));
+            anyhow::anyhow!(
+                "{}:{}:{}: {}",
+                path.display(),
+                line,
+                column,
+                describe_parse_error(e.message())
+            )
+        })?;
+        if let Some(budget) = &config.max_total_cache {
+            parse_size(budget)
+                .with_context(|| format!("{}: invalid max_total_cache", path.display()))?;
+        }
+        for (i, rule) in config.rules.iter().flatten().enumerate() {
+            rule.validate()
+                .with_context(|| format!("{}: invalid rule #{}", path.display(), i + 1))?;
+        }
+        Ok(config)
+    }
+
+    /// Configuration files from lowest to highest precedence, whether or not they exist
+    fn config_files() -> Result<Vec<(&'static str, PathBuf)>> {
+        let mut files = vec![("system", PathBuf::from(SYSTEM_CONFIG))];
+        if let Some(dir) = user_config_dir() {
+            files.push(("user", dir.join("cachekill").join("config.toml")));
         }
+        if let Some(project) = Self::find_config_file()? {
+            files.push(("project", project));
+        }
+        Ok(files)
     }
 
     /// Find the .cachekillrc file in the current directory or parent directories
-    fn find_config_file() -> Result<PathBuf> {
+    fn find_config_file() -> Result<Option<PathBuf>> {
         let mut current_dir = get_current_dir()?;
 
         loop {
             let config_path = current_dir.join(".cachekillrc");
             if config_path.exists() {
-                return Ok(config_path);
+                return Ok(Some(config_path));
             }
 
             if let Some(parent) = current_dir.parent() {
                 current_dir = parent.to_path_buf();
             } else {
-                break;
+                return Ok(None);
             }
         }
+    }
+
+    /// Lay `layer` over this config: values it sets replace ours, lists are appended
+    fn overlay(&mut self, layer: Config, origin: &str) {
+        let origins = &mut self.origins;
+        replace(
+            &mut self.default_lang,
+            layer.default_lang,
+            "default_lang",
+            origin,
+            origins,
+        );
+        replace(
+            &mut self.stale_days,
+            layer.stale_days,
+            "stale_days",
+            origin,
+            origins,
+        );
+        replace(
+            &mut self.safe_delete,
+            layer.safe_delete,
+            "safe_delete",
+            origin,
+            origins,
+        );
+        replace(
+            &mut self.backup_dir,
+            layer.backup_dir,
+            "backup_dir",
+            origin,
+            origins,
+        );
+        append(
+            &mut self.include_paths,
+            layer.include_paths,
+            "include_paths",
+            origin,
+            origins,
+        );
+        append(
+            &mut self.exclude_paths,
+            layer.exclude_paths,
+            "exclude_paths",
+            origin,
+            origins,
+        );
+        replace(
+            &mut self.include_docker,
+            layer.include_docker,
+            "include_docker",
+            origin,
+            origins,
+        );
+        replace(
+            &mut self.include_npx,
+            layer.include_npx,
+            "include_npx",
+            origin,
+            origins,
+        );
+        replace(
+            &mut self.include_js_pm,
+            layer.include_js_pm,
+            "include_js_pm",
+            origin,
+            origins,
+        );
+        replace(
+            &mut self.max_total_cache,
+            layer.max_total_cache,
+            "max_total_cache",
+            origin,
+            origins,
+        );
+        append(&mut self.rules, layer.rules, "rules", origin, origins);
+        replace(&mut self.profile, layer.profile, "profile", origin, origins);
+        // A later layer's profile of the same name replaces the earlier one
+        if let Some(layer_profiles) = layer.profiles {
+            let profiles = self.profiles.get_or_insert_with(BTreeMap::new);
+            for (name, profile) in layer_profiles {
+                profiles.insert(name.clone(), profile);
+                origins
+                    .entry(format!("profiles.{}", name))
+                    .or_default()
+                    .push(origin.to_string());
+            }
+        }
+    }
+
+    /// Apply `CACHEKILL_<KEY>` variables; lists are comma-separated
+    fn apply_env(&mut self, vars: impl IntoIterator<Item = (String, String)>) -> Result<()> {
+        let mut vars: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, _)| {
+                name.strip_prefix(ENV_PREFIX)
+                    .is_some_and(|key| ENV_KEYS.contains(&key.to_ascii_lowercase().as_str()))
+            })
+            .collect();
+        vars.sort();
+
+        for (name, raw) in vars {
+            let key = name[ENV_PREFIX.len()..].to_ascii_lowercase();
+            let value = env_value(&key, &raw).with_context(|| format!("Invalid {}", name))?;
+            let mut table = toml::map::Map::new();
+            table.insert(key, value);
+            let layer: Config = toml::Value::Table(table)
+                .try_into()
+                .with_context(|| format!("Invalid {}", name))?;
+            self.overlay(layer, &format!("env ({})", name));
+        }
+        Ok(())
+    }
+
+    /// Every effective setting after CLI overrides, with the layers that set it
+    pub fn effective(&self, cli_args: &CliArgs) -> Vec<EffectiveValue> {
+        let merged = self.merge_with_cli(cli_args);
+        let origin = |key: &str, flag: Option<&str>| {
+            let mut layers = self
+                .origins
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| vec!["default".to_string()]);
+            if let Some(flag) = flag {
+                let cli = format!("cli ({})", flag);
+                if is_list_key(key) {
+                    layers.push(cli);
+                } else {
+                    layers = vec![cli];
+                }
+            }
+            layers.join(", ")
+        };
+        let set = |set: bool, flag: &'static str| set.then_some(flag);
 
-        // Return a non-existent path if no config file found
-        Ok(PathBuf::from(".cachekillrc"))
+        let values = [
+            (
+                "default_lang",
+                serde_json::json!(merged.lang),
+                set(cli_args.lang.is_some(), "--lang"),
+            ),
+            (
+                "stale_days",
+                serde_json::json!(merged.stale_days),
+                set(cli_args.stale_days.is_some(), "--stale-days"),
+            ),
+            (
+                "safe_delete",
+                serde_json::json!(merged.safe_delete),
+                set(cli_args.safe_delete.is_some(), "--safe-delete"),
+            ),
+            (
+                "backup_dir",
+                serde_json::json!(merged.backup_dir),
+                set(cli_args.backup_dir.is_some(), "--backup-dir"),
+            ),
+            (
+                "include_paths",
+                serde_json::json!(merged.paths),
+                set(cli_args.paths.is_some(), "--paths"),
+            ),
+            (
+                "exclude_paths",
+                serde_json::json!(merged.exclude),
+                set(cli_args.exclude.is_some(), "--exclude"),
+            ),
+            (
+                "include_docker",
+                serde_json::json!(merged.docker),
+                set(cli_args.docker, "--docker"),
+            ),
+            (
+                "include_npx",
+                serde_json::json!(merged.npx),
+                set(cli_args.npx, "--npx"),
+            ),
+            (
+                "include_js_pm",
+                serde_json::json!(merged.js_pm),
+                set(cli_args.js_pm, "--js-pm"),
+            ),
+            (
+                "max_total_cache",
+                serde_json::json!(self.max_total_cache),
+                None,
+            ),
+            ("rules", serde_json::json!(merged.rules), None),
+            ("profile", serde_json::json!(self.profile), None),
+        ];
+
+        values
+            .into_iter()
+            .map(|(key, value, flag)| EffectiveValue {
+                key: key.to_string(),
+                value,
+                origin: Some(origin(key, flag)),
+            })
+            .collect()
     }
 
     /// Merge config with CLI arguments
     pub fn merge_with_cli(&self, cli_args: &CliArgs) -> MergedConfig {
         MergedConfig {
-            list: cli_args.list,
             dry_run: cli_args.dry_run,
             force: cli_args.force,
             json: cli_args.json,
             lang: cli_args
                 .lang
                 .unwrap_or(self.default_lang.unwrap_or(LanguageFilter::Auto)),
-            paths: cli_args
-                .paths
-                .clone()
-                .unwrap_or_else(|| self.include_paths.clone().unwrap_or_default()),
-            exclude: cli_args
-                .exclude
-                .clone()
-                .unwrap_or_else(|| self.exclude_paths.clone().unwrap_or_default()),
+            paths: merge_lists(&self.include_paths, &cli_args.paths),
+            exclude: merge_lists(&self.exclude_paths, &cli_args.exclude),
             stale_days: cli_args.stale_days.unwrap_or(self.stale_days.unwrap_or(14)),
             safe_delete: cli_args
                 .safe_delete
@@ -154,17 +561,222 @@ impl Config {
             }),
             docker: cli_args.docker || self.include_docker.unwrap_or(false),
             npx: cli_args.npx || self.include_npx.unwrap_or(false),
-            restore_last: cli_args.restore_last,
             all: cli_args.all,
-            js_pm: cli_args.js_pm,
+            js_pm: cli_args.js_pm || self.include_js_pm.unwrap_or(false),
+            // Validated when loaded
+            max_total_cache: self
+                .max_total_cache
+                .as_deref()
+                .and_then(|size| parse_size(size).ok()),
+            npx_packages: cli_args.npx_packages.clone().unwrap_or_default(),
+            keep_versions: cli_args.keep_versions,
+            gitignored: cli_args.gitignored,
+            min_size: cli_args.min_size.unwrap_or(DEFAULT_MIN_SIZE),
+            rules: self.rules.clone().unwrap_or_default(),
+            explain: cli_args.explain,
+            free_target: cli_args.free_target,
+            rehydrate: cli_args.rehydrate,
+            kill_holders: cli_args.kill_holders,
         }
     }
 }
 
+/// Directory holding per-user configuration: `$XDG_CONFIG_HOME` or `~/.config`
+fn user_config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+}
+
+/// Keys whose values are appended across layers instead of replaced
+fn is_list_key(key: &str) -> bool {
+    matches!(key, "include_paths" | "exclude_paths" | "rules")
+}
+
+/// Replace `slot` when the layer sets a value
+fn replace<T>(
+    slot: &mut Option<T>,
+    value: Option<T>,
+    key: &str,
+    origin: &str,
+    origins: &mut BTreeMap<String, Vec<String>>,
+) {
+    if value.is_some() {
+        *slot = value;
+        origins.insert(key.to_string(), vec![origin.to_string()]);
+    }
+}
+
+/// Append the layer's items to `slot`, skipping ones already present
+fn append<T: PartialEq>(
+    slot: &mut Option<Vec<T>>,
+    value: Option<Vec<T>>,
+    key: &str,
+    origin: &str,
+    origins: &mut BTreeMap<String, Vec<String>>,
+) {
+    let Some(values) = value else {
+        return;
+    };
+    let layers = origins.entry(key.to_string()).or_insert_with(|| {
+        if slot.as_ref().is_some_and(|list| !list.is_empty()) {
+            vec!["default".to_string()]
+        } else {
+            Vec::new()
+        }
+    });
+    layers.push(origin.to_string());
+
+    let list = slot.get_or_insert_with(Vec::new);
+    for item in values {
+        if !list.contains(&item) {
+            list.push(item);
+        }
+    }
+}
+
+/// Config list followed by the CLI's additions
+fn merge_lists(config: &Option<Vec<String>>, cli: &Option<Vec<String>>) -> Vec<String> {
+    let mut list = config.clone().unwrap_or_default();
+    for item in cli.iter().flatten() {
+        if !list.contains(item) {
+            list.push(item.clone());
+        }
+    }
+    list
+}
+
+/// Convert an environment variable into the TOML value its key expects
+fn env_value(key: &str, raw: &str) -> Result<toml::Value> {
+    let raw = raw.trim();
+    Ok(match key {
+        "stale_days" => toml::Value::Integer(
+            raw.parse()
+                .map_err(|_| anyhow::anyhow!("expected a number of days, got '{}'", raw))?,
+        ),
+        "safe_delete" | "include_docker" | "include_npx" | "include_js_pm" => {
+            toml::Value::Boolean(match raw.to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => true,
+                "0" | "false" | "no" | "off" => false,
+                _ => anyhow::bail!("expected true or false, got '{}'", raw),
+            })
+        }
+        "max_total_cache" => {
+            parse_size(raw)?;
+            toml::Value::String(raw.to_string())
+        }
+        "include_paths" | "exclude_paths" => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        ),
+        _ => toml::Value::String(raw.to_string()),
+    })
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+/// Reword serde's unknown key and variant errors, suggesting the closest valid name
+fn describe_parse_error(message: &str) -> String {
+    let message = message.trim();
+    for (prefix, what) in [("unknown field `", "key"), ("unknown variant `", "value")] {
+        let Some(rest) = message.strip_prefix(prefix) else {
+            continue;
+        };
+        let Some((name, expected)) = rest.split_once('`') else {
+            continue;
+        };
+        let candidates: Vec<&str> = expected.split('`').skip(1).step_by(2).collect();
+        return match suggest(name, &candidates) {
+            Some(candidate) => format!(
+                "unknown {} `{}` (did you mean `{}`?)",
+                what, name, candidate
+            ),
+            None => format!(
+                "unknown {} `{}`, expected one of: {}",
+                what,
+                name,
+                candidates.join(", ")
+            ),
+        };
+    }
+    message.to_string()
+}
+
+/// Handle `config check`: load every layer and report the first problem.
+/// Exits with `CONFIG_ERROR` when the configuration is invalid.
+pub fn handle_config_check(profile: Option<&str>, json: bool) -> Result<()> {
+    let result = Config::load(profile).and_then(|_| Config::config_files());
+    let files: Vec<String> = match &result {
+        Ok(files) => files
+            .iter()
+            .filter(|(_, path)| path.exists())
+            .map(|(layer, path)| format!("{} ({})", layer, path.display()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    let error = result.err().map(|e| format!("{:#}", e));
+
+    if json {
+        let report = serde_json::json!({
+            "valid": error.is_none(),
+            "files": files,
+            "error": error,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if let Some(error) = &error {
+        eprintln!("❌ Invalid configuration: {}", error);
+    } else if files.is_empty() {
+        println!("✅ No configuration files found; using defaults");
+    } else {
+        println!("✅ Configuration is valid:");
+        for file in &files {
+            println!("  {}", file);
+        }
+    }
+
+    if error.is_some() {
+        std::process::exit(exit_codes::CONFIG_ERROR);
+    }
+    Ok(())
+}
+
+/// Handle `config show`: print every effective value, optionally with its origin
+pub fn handle_config_show(config: &Config, cli_args: &CliArgs, show_origin: bool) -> Result<()> {
+    let mut values = config.effective(cli_args);
+    if !show_origin {
+        for value in &mut values {
+            value.origin = None;
+        }
+    }
+
+    if cli_args.json {
+        println!("{}", serde_json::to_string_pretty(&values)?);
+        return Ok(());
+    }
+
+    println!("⚙️  Effective configuration:");
+    for value in &values {
+        match &value.origin {
+            Some(origin) => println!("  {} = {}  # {}", value.key, value.value, origin),
+            None => println!("  {} = {}", value.key, value.value),
+        }
+    }
+    Ok(())
+}
+
 impl Default for MergedConfig {
     fn default() -> Self {
         Self {
-            list: false,
             dry_run: false,
             force: false,
             json: false,
@@ -176,9 +788,18 @@ impl Default for MergedConfig {
             backup_dir: "~/.cachekill-backup".to_string(),
             docker: false,
             npx: false,
-            restore_last: false,
             all: false,
             js_pm: false,
+            npx_packages: Vec::new(),
+            keep_versions: None,
+            gitignored: false,
+            min_size: DEFAULT_MIN_SIZE,
+            rules: Vec::new(),
+            explain: false,
+            free_target: None,
+            rehydrate: None,
+            kill_holders: false,
+            max_total_cache: None,
         }
     }
 }
@@ -202,6 +823,11 @@ impl MergedConfig {
         should_exclude_path(path, &self.exclude)
     }
 
+    /// Whether this run only targets individual NPX packages
+    pub fn npx_selection_only(&self) -> bool {
+        self.npx && (!self.npx_packages.is_empty() || self.keep_versions.is_some())
+    }
+
     /// Check if a path should be processed (included and not excluded)
     pub fn should_process_path(&self, path: &std::path::Path) -> bool {
         self.should_include_path(path) && !self.should_exclude_path(path)
@@ -226,7 +852,6 @@ mod tests {
     fn test_config_merge() {
         let config = Config::default();
         let cli_args = CliArgs {
-            list: false,
             dry_run: true,
             force: false,
             json: false,
@@ -238,9 +863,16 @@ mod tests {
             backup_dir: None,
             docker: true,
             npx: false,
-            restore_last: false,
             all: false,
             js_pm: false,
+            npx_packages: None,
+            keep_versions: None,
+            gitignored: false,
+            min_size: None,
+            explain: false,
+            free_target: None,
+            rehydrate: None,
+            kill_holders: false,
         };
 
         let merged = config.merge_with_cli(&cli_args);
@@ -267,16 +899,128 @@ include_docker = true
         // Change to temp directory to test config loading
         std::env::set_current_dir(temp_dir.path()).unwrap();
 
-        let config = Config::load().unwrap();
+        let config = Config::load(None).unwrap();
         assert_eq!(config.stale_days, Some(7));
         assert_eq!(config.safe_delete, Some(false));
         assert_eq!(config.include_docker, Some(true));
     }
 
+    #[test]
+    fn test_layers_override_values_and_append_lists() {
+        let layer = |content: &str| -> Config { toml::from_str(content).unwrap() };
+        let mut config = Config::default();
+        config.overlay(
+            layer("stale_days = 30\nexclude_paths = [\"**/vendor\"]"),
+            "user",
+        );
+        config.overlay(
+            layer("stale_days = 7\nexclude_paths = [\"**/dist\", \"**/vendor\"]"),
+            "project",
+        );
+
+        assert_eq!(config.stale_days, Some(7));
+        assert_eq!(config.origins["stale_days"], vec!["project"]);
+        let exclude = config.exclude_paths.clone().unwrap();
+        assert_eq!(&exclude[exclude.len() - 2..], ["**/vendor", "**/dist"]);
+        assert_eq!(
+            config.origins["exclude_paths"],
+            vec!["default", "user", "project"]
+        );
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config = Config::default();
+        let vars = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        };
+        config
+            .apply_env(vars(&[
+                ("CACHEKILL_STALE_DAYS", "3"),
+                ("CACHEKILL_INCLUDE_DOCKER", "on"),
+                ("CACHEKILL_INCLUDE_PATHS", "**/out, **/tmp"),
+                ("CACHEKILL_CI", "ignored"),
+            ]))
+            .unwrap();
+        assert_eq!(config.stale_days, Some(3));
+        assert_eq!(config.include_docker, Some(true));
+        assert_eq!(
+            config.include_paths,
+            Some(vec!["**/out".to_string(), "**/tmp".to_string()])
+        );
+        assert_eq!(
+            config.origins["stale_days"],
+            vec!["env (CACHEKILL_STALE_DAYS)"]
+        );
+
+        assert!(config
+            .apply_env(vars(&[("CACHEKILL_DEFAULT_LANG", "cobol")]))
+            .is_err());
+        assert!(config
+            .apply_env(vars(&[("CACHEKILL_SAFE_DELETE", "maybe")]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let path = Path::new(".cachekillrc");
+        let error = |content: &str| Config::parse(content, path).unwrap_err().to_string();
+
+        assert_eq!(
+            error("stale_days = 7\nexlude_paths = [\"**/dist\"]\n"),
+            ".cachekillrc:2:1: unknown key `exlude_paths` (did you mean `exclude_paths`?)"
+        );
+        assert_eq!(
+            error("default_lang = \"rusty\""),
+            ".cachekillrc:1:16: unknown value `rusty` (did you mean `rust`?)"
+        );
+        assert!(error("max_total_cache = \"lots\"").contains("invalid max_total_cache"));
+        assert!(error("[[rules]]\naction = \"delete\"\nolder = \"3d\"\n")
+            .contains("did you mean `older_than`?"));
+        assert!(
+            error("[[rules]]\naction = \"delete\"\nolder_than = \"soon\"\n")
+                .contains("invalid rule #1")
+        );
+        assert!(Config::parse("stale_days = 7", path).is_ok());
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut config = Config::default();
+        let file = Config::parse(
+            r#"
+stale_days = 14
+exclude_paths = ["**/keep"]
+
+[profiles.ci]
+stale_days = 0
+include_js_pm = true
+exclude_paths = ["**/vendor"]
+"#,
+            Path::new(".cachekillrc"),
+        )
+        .unwrap();
+        config.overlay(file, "project");
+        config.apply_profile("ci").unwrap();
+
+        assert_eq!(config.stale_days, Some(0));
+        assert_eq!(config.origins["stale_days"], vec!["profile (ci)"]);
+        let exclude = config.exclude_paths.clone().unwrap();
+        assert!(exclude.contains(&"**/keep".to_string()));
+        assert!(exclude.contains(&"**/vendor".to_string()));
+
+        assert!(config.merge_with_cli(&CliArgs::default()).js_pm);
+
+        let error = config.apply_profile("cl").unwrap_err().to_string();
+        assert_eq!(error, "unknown profile `cl` (did you mean `ci`?)");
+    }
+
     #[test]
     fn test_should_process_path() {
         let config = MergedConfig {
-            list: false,
             dry_run: false,
             force: false,
             json: false,
@@ -288,9 +1032,18 @@ include_docker = true
             backup_dir: ".cachekill-backup".to_string(),
             docker: false,
             npx: false,
-            restore_last: false,
             all: false,
             js_pm: false,
+            npx_packages: vec![],
+            keep_versions: None,
+            gitignored: false,
+            min_size: DEFAULT_MIN_SIZE,
+            rules: vec![],
+            explain: false,
+            free_target: None,
+            rehydrate: None,
+            kill_holders: false,
+            max_total_cache: None,
         };
 
         let node_modules = std::path::Path::new("/project/node_modules");
@@ -0,0 +1,525 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache_entry::CacheKind;
+use crate::regen::RegenSource;
+use crate::util::expand_home;
+
+/// Detectors shipped with cachekill
+const BUILTIN_DETECTORS: &str = include_str!("builtin.toml");
+
+/// A file whose content identifies an ecosystem (e.g. `torch` in requirements.txt)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentMarker {
+    pub file: String,
+    pub words: Vec<String>,
+}
+
+/// A regenerate command used when the project holds a given file (usually a lockfile)
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegenerateWith {
+    pub file: String,
+    pub command: String,
+}
+
+/// A cache outside the project, optionally relocated by an environment variable
+#[derive(Debug, Clone, Deserialize)]
+pub struct GlobalCache {
+    pub path: String,
+    #[serde(default)]
+    pub env: Option<String>,
+}
+
+impl GlobalCache {
+    /// Resolve the cache path, preferring the environment override
+    pub fn resolve(&self) -> PathBuf {
+        self.env
+            .as_deref()
+            .and_then(|name| std::env::var(name).ok())
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| expand_home(&value))
+            .unwrap_or_else(|| expand_home(&self.path))
+    }
+}
+
+impl ContentMarker {
+    /// Check whether the marker file mentions any of the words
+    fn found_in(&self, root: &Path) -> bool {
+        fs::read_to_string(root.join(&self.file))
+            .map(|content| self.words.iter().any(|w| content.contains(w.as_str())))
+            .unwrap_or(false)
+    }
+}
+
+/// Evidence that an ambiguous cache directory (`build`, `out`, `target`, ...) was
+/// produced by this ecosystem rather than holding project files
+#[derive(Debug, Clone, Deserialize)]
+pub struct CacheCheck {
+    /// Cache directory, as listed in `caches`
+    pub cache: String,
+    /// Files or directories the build system leaves inside the cache
+    #[serde(default)]
+    pub inside: Vec<String>,
+    /// Build configuration files in the project root
+    #[serde(default)]
+    pub project: Vec<String>,
+    /// Project files whose content names the build tool
+    #[serde(default)]
+    pub project_contains: Vec<ContentMarker>,
+}
+
+impl CacheCheck {
+    fn has_evidence(&self) -> bool {
+        !self.inside.is_empty() || !self.project.is_empty() || !self.project_contains.is_empty()
+    }
+
+    /// Check whether any piece of evidence is present
+    fn satisfied(&self, project_root: &Path, cache_dir: &Path) -> bool {
+        self.inside.iter().any(|name| cache_dir.join(name).exists())
+            || self
+                .project
+                .iter()
+                .any(|name| project_root.join(name).exists())
+            || self
+                .project_contains
+                .iter()
+                .any(|m| m.found_in(project_root))
+    }
+
+    /// Human-readable list of the evidence looked for
+    fn expected(&self) -> String {
+        let mut expected: Vec<String> = self
+            .inside
+            .iter()
+            .map(|name| format!("{}/{}", self.cache, name))
+            .collect();
+        expected.extend(self.project.iter().cloned());
+        expected.extend(
+            self.project_contains
+                .iter()
+                .map(|m| format!("{} mentioning {}", m.file, m.words.join("/"))),
+        );
+        expected.join(", ")
+    }
+}
+
+/// Declarative description of one ecosystem's caches
+#[derive(Debug, Clone, Deserialize)]
+pub struct Detector {
+    pub name: String,
+    pub kind: CacheKind,
+    #[serde(default)]
+    pub markers: Vec<String>,
+    #[serde(default)]
+    pub contains: Vec<ContentMarker>,
+    #[serde(default)]
+    pub caches: Vec<String>,
+    #[serde(default)]
+    pub nested: Vec<String>,
+    #[serde(default)]
+    pub global: Vec<GlobalCache>,
+    #[serde(default)]
+    pub regenerate: Option<String>,
+    /// Commands chosen by the files present; the first match replaces `regenerate`
+    #[serde(default)]
+    pub regenerate_with: Vec<RegenerateWith>,
+    /// What `clean --rehydrate` runs instead, when restoring downloads is enough
+    #[serde(default)]
+    pub rehydrate: Option<String>,
+    /// Caches filled from the network (packages, models); the rest are rebuilt locally
+    #[serde(default)]
+    pub downloads: Vec<String>,
+    /// Only used with `--all`
+    #[serde(default)]
+    pub requires_all: bool,
+    /// Caches with generic names that must be verified before they are proposed
+    #[serde(default)]
+    pub verify: Vec<CacheCheck>,
+}
+
+impl Detector {
+    /// Check whether the project root carries this detector's markers
+    pub fn matches(&self, project_root: &Path) -> bool {
+        self.markers
+            .iter()
+            .any(|marker| project_root.join(marker).exists())
+            || self
+                .contains
+                .iter()
+                .any(|marker| marker.found_in(project_root))
+    }
+
+    /// Why `path` must not be proposed for removal, or `None` when it passes
+    /// (or has no check)
+    pub fn verify(&self, project_root: &Path, path: &Path) -> Option<String> {
+        let check = self
+            .verify
+            .iter()
+            .find(|check| project_root.join(&check.cache) == path)?;
+
+        if check.has_evidence() && !check.satisfied(project_root, path) {
+            return Some(format!(
+                "not verified as a {} cache: expected {}",
+                self.name,
+                check.expected()
+            ));
+        }
+        None
+    }
+
+    /// Command that regenerates this project's caches, picked by its lockfile
+    pub fn regenerate_for(&self, project_root: &Path) -> Option<String> {
+        self.regenerate_with
+            .iter()
+            .find(|with| project_root.join(&with.file).exists())
+            .map(|with| with.command.clone())
+            .or_else(|| self.regenerate.clone())
+    }
+
+    /// Where the cache at `path` comes back from; global caches are downloads
+    pub fn regen_source(&self, path: &Path) -> RegenSource {
+        if self.global.iter().any(|g| path == g.resolve())
+            || self.downloads.iter().any(|d| path.ends_with(d))
+        {
+            RegenSource::Network
+        } else {
+            RegenSource::Build
+        }
+    }
+
+    /// Candidate cache paths for a project, existing or not
+    pub fn candidate_paths(&self, project_root: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.caches.iter().map(|c| project_root.join(c)).collect();
+
+        if !self.nested.is_empty() {
+            if let Ok(entries) = fs::read_dir(project_root) {
+                for entry in entries.flatten() {
+                    if entry.path().is_dir() {
+                        paths.extend(self.nested.iter().map(|n| entry.path().join(n)));
+                    }
+                }
+            }
+        }
+
+        paths.extend(self.global.iter().map(GlobalCache::resolve));
+        paths
+    }
+
+    /// Check whether `path` is one of this detector's project caches
+    fn owns_path(&self, path: &Path) -> bool {
+        self.caches
+            .iter()
+            .chain(self.nested.iter())
+            .any(|cache| path.ends_with(cache))
+            || self.global.iter().any(|g| path == g.resolve())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DetectorFile {
+    #[serde(default)]
+    detector: Vec<Detector>,
+}
+
+/// Ordered set of detectors: built-ins first, then user detectors
+#[derive(Debug, Clone)]
+pub struct DetectorRegistry {
+    detectors: Vec<Detector>,
+}
+
+impl DetectorRegistry {
+    /// Built-in detectors only
+    pub fn builtin() -> Self {
+        let file: DetectorFile =
+            toml::from_str(BUILTIN_DETECTORS).expect("built-in detectors are valid TOML");
+        Self {
+            detectors: file.detector,
+        }
+    }
+
+    /// Built-in detectors plus `~/.config/cachekill/detectors/*.toml`.
+    /// Files that fail to parse are reported and skipped.
+    pub fn load() -> Self {
+        let mut registry = Self::builtin();
+        if let Some(dir) = user_detectors_dir() {
+            for (path, result) in read_detector_dir(&dir) {
+                match result {
+                    Ok(detectors) => registry.extend(detectors),
+                    Err(e) => {
+                        eprintln!("Warning: skipping detectors in {}: {:#}", path.display(), e)
+                    }
+                }
+            }
+        }
+        registry
+    }
+
+    /// Add detectors, replacing any existing detector with the same name
+    pub fn extend(&mut self, detectors: Vec<Detector>) {
+        for detector in detectors {
+            match self.detectors.iter_mut().find(|d| d.name == detector.name) {
+                Some(existing) => *existing = detector,
+                None => self.detectors.push(detector),
+            }
+        }
+    }
+
+    pub fn detectors(&self) -> &[Detector] {
+        &self.detectors
+    }
+
+    /// Detectors whose markers are present in the project root
+    pub fn matching(&self, project_root: &Path) -> Vec<&Detector> {
+        self.detectors
+            .iter()
+            .filter(|d| d.matches(project_root))
+            .collect()
+    }
+
+    /// Kind for a path found outside a detector (e.g. `--paths`), by its cache name
+    pub fn kind_for_path(&self, path: &Path) -> CacheKind {
+        self.detectors
+            .iter()
+            .filter(|d| !d.requires_all)
+            .find(|d| d.owns_path(path))
+            .map(|d| d.kind)
+            .unwrap_or(CacheKind::Generic)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/cachekill/detectors`, defaulting to `~/.config/cachekill/detectors`
+pub fn user_detectors_dir() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config_home.join("cachekill").join("detectors"))
+}
+
+/// Parse every `*.toml` in `dir`, in file name order
+fn read_detector_dir(dir: &Path) -> Vec<(PathBuf, Result<Vec<Detector>>)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .map(|path| {
+            let result = fs::read_to_string(&path)
+                .context("Failed to read detector file")
+                .and_then(|content| {
+                    toml::from_str::<DetectorFile>(&content)
+                        .map(|file| file.detector)
+                        .context("Invalid detector file")
+                });
+            (path, result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_detectors_parse() {
+        let registry = DetectorRegistry::builtin();
+        let names: Vec<&str> = registry
+            .detectors()
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["javascript", "python", "rust", "java", "ml", "generic"]
+        );
+    }
+
+    #[test]
+    fn test_rehydrate_overrides_regenerate() {
+        let registry = DetectorRegistry::builtin();
+        let rust = registry
+            .detectors()
+            .iter()
+            .find(|d| d.name == "rust")
+            .unwrap();
+        let javascript = &registry.detectors()[0];
+
+        assert_eq!(rust.regenerate.as_deref(), Some("cargo build"));
+        assert_eq!(rust.rehydrate.as_deref(), Some("cargo fetch"));
+        assert_eq!(javascript.rehydrate, None);
+    }
+
+    #[test]
+    fn test_regenerate_follows_lockfile() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let registry = DetectorRegistry::builtin();
+        let javascript = &registry.detectors()[0];
+
+        assert_eq!(
+            javascript.regenerate_for(root).as_deref(),
+            Some("npm install")
+        );
+        fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        assert_eq!(
+            javascript.regenerate_for(root).as_deref(),
+            Some("pnpm install --frozen-lockfile")
+        );
+
+        assert_eq!(
+            javascript.regen_source(&root.join("node_modules")),
+            RegenSource::Network
+        );
+        assert_eq!(
+            javascript.regen_source(&root.join(".next")),
+            RegenSource::Build
+        );
+    }
+
+    #[test]
+    fn test_kind_for_path_matches_whole_components() {
+        let registry = DetectorRegistry::builtin();
+        assert_eq!(
+            registry.kind_for_path(Path::new("node_modules")),
+            CacheKind::JavaScript
+        );
+        assert_eq!(
+            registry.kind_for_path(Path::new("app/__pycache__")),
+            CacheKind::Python
+        );
+        assert_eq!(
+            registry.kind_for_path(Path::new("/work/target")),
+            CacheKind::Rust
+        );
+        // Substrings no longer count: only whole path components match
+        assert_eq!(
+            registry.kind_for_path(Path::new("/work/my-target-dir")),
+            CacheKind::Generic
+        );
+    }
+
+    #[test]
+    fn test_content_marker() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("requirements.txt"), "torch==2.0\n").unwrap();
+        let registry = DetectorRegistry::builtin();
+        let names: Vec<&str> = registry
+            .matching(temp_dir.path())
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["python", "ml"]);
+    }
+
+    #[test]
+    fn test_user_detectors_extend_and_override() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("elixir.toml"),
+            r#"
+[[detector]]
+name = "elixir"
+kind = "generic"
+markers = ["mix.exs"]
+caches = ["_build", "deps"]
+regenerate = "mix deps.get"
+
+[[detector]]
+name = "rust"
+kind = "rust"
+markers = ["Cargo.toml"]
+caches = ["target"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("broken.toml"),
+            "[[detector]]\nname = 1\n",
+        )
+        .unwrap();
+
+        let mut registry = DetectorRegistry::builtin();
+        let results = read_detector_dir(temp_dir.path());
+        assert_eq!(results.len(), 2);
+        for (_, result) in results {
+            if let Ok(detectors) = result {
+                registry.extend(detectors);
+            }
+        }
+
+        let rust = registry
+            .detectors()
+            .iter()
+            .find(|d| d.name == "rust")
+            .unwrap();
+        assert_eq!(rust.caches, vec!["target"]);
+        let elixir = registry.detectors().last().unwrap();
+        assert_eq!(elixir.name, "elixir");
+        assert_eq!(elixir.regenerate.as_deref(), Some("mix deps.get"));
+    }
+
+    fn detector(registry: &DetectorRegistry, name: &str) -> Detector {
+        registry
+            .detectors()
+            .iter()
+            .find(|d| d.name == name)
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_target_requires_cargo_tag() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let target = root.join("target");
+        fs::create_dir_all(&target).unwrap();
+        let rust = detector(&DetectorRegistry::builtin(), "rust");
+
+        let reason = rust.verify(root, &target).unwrap();
+        assert!(reason.contains("target/CACHEDIR.TAG"));
+
+        fs::write(
+            target.join("CACHEDIR.TAG"),
+            "Signature: 8a477f597d28d172789f06886806bc55",
+        )
+        .unwrap();
+        assert_eq!(rust.verify(root, &target), None);
+
+        // Caches without a check are never refused
+        assert_eq!(rust.verify(root, &root.join(".cargo")), None);
+    }
+
+    #[test]
+    fn test_build_requires_a_build_system() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let build = root.join("build");
+        fs::create_dir_all(&build).unwrap();
+        fs::write(root.join("package.json"), r#"{"scripts":{"test":"jest"}}"#).unwrap();
+        let registry = DetectorRegistry::builtin();
+        let javascript = detector(&registry, "javascript");
+        let java = detector(&registry, "java");
+
+        assert!(javascript.verify(root, &build).is_some());
+        assert!(java.verify(root, &build).is_some());
+
+        fs::write(
+            root.join("package.json"),
+            r#"{"dependencies":{"react-scripts":"5.0.1"}}"#,
+        )
+        .unwrap();
+        assert_eq!(javascript.verify(root, &build), None);
+
+        fs::write(root.join("build.gradle"), "").unwrap();
+        assert_eq!(java.verify(root, &build), None);
+    }
+}
@@ -1,8 +1,13 @@
+use crate::activity::ProjectActivity;
 use crate::cache_entry::{CacheKind, LanguageFilter};
+use crate::cachedir_tag;
 use crate::config::MergedConfig;
+use crate::detectors::{Detector, DetectorRegistry};
+use crate::git::{self, PathStatus};
+use crate::regen::RegenSource;
 use crate::util::{get_current_dir, is_dir, path_exists};
 use anyhow::Result;
-use std::fs;
+use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
 
 /// Detected project type
@@ -17,74 +22,70 @@ pub enum ProjectType {
     Unknown,
 }
 
+/// A cache path found by a detector, with the kind it was found as
+#[derive(Debug, Clone)]
+pub struct DiscoveredCache {
+    pub path: PathBuf,
+    pub kind: CacheKind,
+    /// Name of the detector that found it (`paths` for `--paths`)
+    pub detector: String,
+    pub regenerate: Option<String>,
+    /// Command `clean --rehydrate` runs, when it differs from `regenerate`
+    pub rehydrate: Option<String>,
+    /// Where the cache comes back from, when its detector says
+    pub regen_source: Option<RegenSource>,
+    /// Project whose detector found the cache, where `regenerate` runs
+    pub project_root: Option<PathBuf>,
+    /// Set when a generic-named cache could not be verified; the entry is skipped
+    pub skip_reason: Option<String>,
+    /// What the enclosing git repository says about the path, if any
+    pub git: Option<PathStatus>,
+    /// When the project owning this cache was last active (project caches only)
+    pub project_last_active: Option<DateTime<Utc>>,
+    /// Detectors that recognized the owning project, for `[[rules]]`
+    pub project_types: Vec<String>,
+}
+
 /// Cache discovery result
 #[derive(Debug, Clone)]
 pub struct DiscoveryResult {
     #[allow(dead_code)]
     pub project_type: ProjectType,
-    pub cache_entries: Vec<PathBuf>,
+    pub cache_entries: Vec<DiscoveredCache>,
     #[allow(dead_code)]
     pub project_root: PathBuf,
+    /// When the project was last worked on, for recognized projects
+    pub activity: Option<ProjectActivity>,
 }
 
 impl ProjectType {
     /// Detect project type from directory contents
     pub fn detect(project_root: &Path) -> Result<Self> {
-        let mut types = Vec::new();
-
-        // Check for JavaScript/TypeScript projects
-        if project_root.join("package.json").exists()
-            || project_root.join("yarn.lock").exists()
-            || project_root.join("pnpm-lock.yaml").exists()
-            || project_root.join("bun.lockb").exists()
-        {
-            types.push(ProjectType::JavaScript);
-        }
-
-        // Check for Python projects
-        if project_root.join("pyproject.toml").exists()
-            || project_root.join("requirements.txt").exists()
-            || project_root.join("setup.py").exists()
-            || project_root.join("Pipfile").exists()
-            || project_root.join("poetry.lock").exists()
-        {
-            types.push(ProjectType::Python);
-        }
-
-        // Check for Rust projects
-        if project_root.join("Cargo.toml").exists() {
-            types.push(ProjectType::Rust);
-        }
-
-        // Check for Java projects
-        if project_root.join("pom.xml").exists()
-            || project_root.join("build.gradle").exists()
-            || project_root.join("build.gradle.kts").exists()
-            || project_root.join("gradlew").exists()
-        {
-            types.push(ProjectType::Java);
-        }
+        let registry = DetectorRegistry::builtin();
+        Ok(Self::from_detectors(&registry.matching(project_root)))
+    }
 
-        // Check for ML/AI projects
-        if project_root.join("requirements.txt").exists()
-            && (fs::read_to_string(project_root.join("requirements.txt"))
-                .unwrap_or_default()
-                .contains("torch")
-                || fs::read_to_string(project_root.join("requirements.txt"))
-                    .unwrap_or_default()
-                    .contains("tensorflow")
-                || fs::read_to_string(project_root.join("requirements.txt"))
-                    .unwrap_or_default()
-                    .contains("huggingface"))
-            || project_root.join(".dvc").exists()
-        {
-            types.push(ProjectType::MachineLearning);
+    /// Project type implied by the detectors whose markers matched
+    fn from_detectors(detectors: &[&Detector]) -> Self {
+        let mut types: Vec<ProjectType> = Vec::new();
+        for detector in detectors {
+            let project_type = match detector.kind {
+                CacheKind::JavaScript => ProjectType::JavaScript,
+                CacheKind::Python => ProjectType::Python,
+                CacheKind::Rust => ProjectType::Rust,
+                CacheKind::Java => ProjectType::Java,
+                CacheKind::MachineLearning => ProjectType::MachineLearning,
+                _ => continue,
+            };
+            if !types.contains(&project_type) {
+                types.push(project_type);
+            }
         }
 
         match types.len() {
-            0 => Ok(ProjectType::Unknown),
-            1 => Ok(types[0].clone()),
-            _ => Ok(ProjectType::Mixed),
+            0 => ProjectType::Unknown,
+            1 => types.remove(0),
+            _ => ProjectType::Mixed,
         }
     }
 
@@ -117,267 +118,208 @@ impl ProjectType {
 impl DiscoveryResult {
     /// Discover cache entries in the project
     pub fn discover(config: &MergedConfig) -> Result<Self> {
-        let project_root = get_current_dir()?;
-        let project_type = ProjectType::detect(&project_root)?;
-
-        let mut cache_entries = Vec::new();
-
-        // Discover caches based on project type and language filter
-        // Always include project-type-specific caches when lang is Auto
-        let should_discover_js =
-            config.lang == LanguageFilter::Auto || config.lang == LanguageFilter::JavaScript;
-        let should_discover_py =
-            config.lang == LanguageFilter::Auto || config.lang == LanguageFilter::Python;
-        let should_discover_rust =
-            config.lang == LanguageFilter::Auto || config.lang == LanguageFilter::Rust;
-        let should_discover_java =
-            config.lang == LanguageFilter::Auto || config.lang == LanguageFilter::Java;
-        let should_discover_ml =
-            config.lang == LanguageFilter::Auto || config.lang == LanguageFilter::MachineLearning;
-
-        // For Auto mode, also include project-type-specific caches
-        if config.lang == LanguageFilter::Auto {
-            match project_type {
-                ProjectType::JavaScript => {
-                    cache_entries.extend(Self::discover_js_caches(&project_root, config)?);
-                }
-                ProjectType::Python => {
-                    cache_entries.extend(Self::discover_py_caches(&project_root, config)?);
-                }
-                ProjectType::Rust => {
-                    cache_entries.extend(Self::discover_rust_caches(&project_root, config)?);
-                }
-                ProjectType::Java => {
-                    cache_entries.extend(Self::discover_java_caches(&project_root, config)?);
-                }
-                ProjectType::MachineLearning => {
-                    cache_entries.extend(Self::discover_ml_caches(&project_root, config)?);
-                }
-                ProjectType::Mixed => {
-                    // For mixed projects, include all relevant caches
-                    cache_entries.extend(Self::discover_js_caches(&project_root, config)?);
-                    cache_entries.extend(Self::discover_py_caches(&project_root, config)?);
-                    cache_entries.extend(Self::discover_rust_caches(&project_root, config)?);
-                    cache_entries.extend(Self::discover_java_caches(&project_root, config)?);
-                    cache_entries.extend(Self::discover_ml_caches(&project_root, config)?);
-                }
-                ProjectType::Unknown => {
-                    // For unknown projects, try to discover all caches
-                    cache_entries.extend(Self::discover_js_caches(&project_root, config)?);
-                    cache_entries.extend(Self::discover_py_caches(&project_root, config)?);
-                    cache_entries.extend(Self::discover_rust_caches(&project_root, config)?);
-                    cache_entries.extend(Self::discover_java_caches(&project_root, config)?);
-                    cache_entries.extend(Self::discover_ml_caches(&project_root, config)?);
-                }
-            }
-        } else {
-            // For specific language filters, only include those caches
-            if should_discover_js {
-                cache_entries.extend(Self::discover_js_caches(&project_root, config)?);
-            }
-            if should_discover_py {
-                cache_entries.extend(Self::discover_py_caches(&project_root, config)?);
-            }
-            if should_discover_rust {
-                cache_entries.extend(Self::discover_rust_caches(&project_root, config)?);
-            }
-            if should_discover_java {
-                cache_entries.extend(Self::discover_java_caches(&project_root, config)?);
-            }
-            if should_discover_ml {
-                cache_entries.extend(Self::discover_ml_caches(&project_root, config)?);
-            }
-        }
-
-        if config.all {
-            cache_entries.extend(Self::discover_generic_caches(&project_root, config)?);
-        }
-
-        // Add custom paths if specified
-        if !config.paths.is_empty() {
-            cache_entries.extend(Self::discover_custom_paths(&project_root, config)?);
-        }
-
-        Ok(Self {
-            project_type,
-            cache_entries,
-            project_root,
-        })
+        Self::discover_in(get_current_dir()?, &DetectorRegistry::load(), config)
     }
 
-    /// Discover JavaScript/TypeScript caches
-    fn discover_js_caches(project_root: &Path, config: &MergedConfig) -> Result<Vec<PathBuf>> {
-        let mut caches = Vec::new();
-
-        let js_cache_patterns = vec![
-            "node_modules",
-            ".next",
-            ".nuxt",
-            ".vite",
-            ".cache",
-            "dist",
-            "coverage",
-            ".turbo",
-            ".parcel-cache",
-            "build",
-            "out",
-            ".next/cache",
-            ".nuxt/dist",
-        ];
-
-        for pattern in js_cache_patterns {
-            let cache_path = project_root.join(pattern);
-            if path_exists(&cache_path) && is_dir(&cache_path) {
-                if config.should_process_path(&cache_path) {
-                    caches.push(cache_path);
-                }
+    /// Discover cache entries in the project at `project_root`
+    pub fn discover_in(
+        project_root: PathBuf,
+        registry: &DetectorRegistry,
+        config: &MergedConfig,
+    ) -> Result<Self> {
+        let matched = registry.matching(&project_root);
+        let project_type = ProjectType::from_detectors(&matched);
+
+        // Auto mode uses the detectors whose markers matched (all of them for unknown
+        // projects); a language filter selects detectors by kind
+        let mut detectors: Vec<&Detector> = match lang_kind(config.lang) {
+            None if matched.iter().any(|d| !d.requires_all) => {
+                matched.into_iter().filter(|d| !d.requires_all).collect()
             }
-        }
-
-        Ok(caches)
-    }
-
-    /// Discover Python caches
-    fn discover_py_caches(project_root: &Path, config: &MergedConfig) -> Result<Vec<PathBuf>> {
-        let mut caches = Vec::new();
+            None => registry
+                .detectors()
+                .iter()
+                .filter(|d| !d.requires_all)
+                .collect(),
+            Some(kind) => registry
+                .detectors()
+                .iter()
+                .filter(|d| !d.requires_all && d.kind == kind)
+                .collect(),
+        };
 
-        let py_cache_patterns = vec![
-            "__pycache__",
-            ".pytest_cache",
-            ".venv",
-            "venv",
-            ".tox",
-            ".mypy_cache",
-            ".ruff_cache",
-            ".pip-cache",
-            ".coverage",
-            "htmlcov",
-            ".pytest_cache",
-        ];
-
-        for pattern in py_cache_patterns {
-            let cache_path = project_root.join(pattern);
-            if path_exists(&cache_path) && is_dir(&cache_path) {
-                if config.should_process_path(&cache_path) {
-                    caches.push(cache_path);
-                }
-            }
+        if config.all {
+            detectors.extend(registry.detectors().iter().filter(|d| d.requires_all));
         }
 
-        // Also check for __pycache__ directories in subdirectories
-        if let Ok(entries) = fs::read_dir(project_root) {
-            for entry in entries.flatten() {
-                if entry.path().is_dir() {
-                    let pycache_path = entry.path().join("__pycache__");
-                    if path_exists(&pycache_path) && is_dir(&pycache_path) {
-                        if config.should_process_path(&pycache_path) {
-                            caches.push(pycache_path);
-                        }
-                    }
+        let mut cache_entries: Vec<DiscoveredCache> = Vec::new();
+        if config.gitignored {
+            // Every ignored directory is a candidate; the repository vouches for it
+            detectors.clear();
+            for path in git::ignored_dirs(&project_root) {
+                if is_dir(&path) && config.should_process_path(&path) {
+                    push_unique(
+                        &mut cache_entries,
+                        DiscoveredCache {
+                            kind: registry.kind_for_path(&path),
+                            path,
+                            detector: "gitignored".to_string(),
+                            regenerate: None,
+                            rehydrate: None,
+                            regen_source: None,
+                            project_root: None,
+                            skip_reason: None,
+                            git: None,
+                            project_last_active: None,
+                            project_types: Vec::new(),
+                        },
+                    );
                 }
             }
         }
 
-        Ok(caches)
-    }
-
-    /// Discover Rust caches
-    fn discover_rust_caches(project_root: &Path, config: &MergedConfig) -> Result<Vec<PathBuf>> {
-        let mut caches = Vec::new();
-
-        let rust_cache_patterns = vec!["target", ".cargo"];
-
-        for pattern in rust_cache_patterns {
-            let cache_path = project_root.join(pattern);
-            if path_exists(&cache_path) && is_dir(&cache_path) {
-                if config.should_process_path(&cache_path) {
-                    caches.push(cache_path);
+        for detector in detectors {
+            for path in detector.candidate_paths(&project_root) {
+                if path_exists(&path) && is_dir(&path) && config.should_process_path(&path) {
+                    let skip_reason = detector.verify(&project_root, &path);
+                    let regen_source = Some(detector.regen_source(&path));
+                    push_unique(
+                        &mut cache_entries,
+                        DiscoveredCache {
+                            path,
+                            kind: detector.kind,
+                            detector: detector.name.clone(),
+                            regenerate: detector.regenerate_for(&project_root),
+                            rehydrate: detector.rehydrate.clone(),
+                            regen_source,
+                            project_root: Some(project_root.clone()),
+                            skip_reason,
+                            git: None,
+                            project_last_active: None,
+                            project_types: Vec::new(),
+                        },
+                    );
                 }
             }
         }
 
-        Ok(caches)
-    }
-
-    /// Discover Java caches
-    fn discover_java_caches(project_root: &Path, config: &MergedConfig) -> Result<Vec<PathBuf>> {
-        let mut caches = Vec::new();
-
-        let java_cache_patterns = vec![".gradle", "build", "target", ".m2"];
-
-        for pattern in java_cache_patterns {
-            let cache_path = project_root.join(pattern);
-            if path_exists(&cache_path) && is_dir(&cache_path) {
-                if config.should_process_path(&cache_path) {
-                    caches.push(cache_path);
-                }
+        // Directories tagged with CACHEDIR.TAG declare themselves caches, so
+        // they need no detector or verification
+        let tag_kind = lang_kind(config.lang);
+        for path in cachedir_tag::find_tagged(&project_root, cachedir_tag::MAX_DEPTH) {
+            let kind = registry.kind_for_path(&path);
+            if tag_kind.map_or(true, |k| k == kind) && config.should_process_path(&path) {
+                push_unique(
+                    &mut cache_entries,
+                    DiscoveredCache {
+                        path,
+                        kind,
+                        detector: "cachedir-tag".to_string(),
+                        regenerate: None,
+                        rehydrate: None,
+                        regen_source: None,
+                        project_root: None,
+                        skip_reason: None,
+                        git: None,
+                        project_last_active: None,
+                        project_types: Vec::new(),
+                    },
+                );
             }
         }
 
-        // Check for Maven repository in home directory
-        if let Some(home) = dirs::home_dir() {
-            let m2_repo = home.join(".m2").join("repository");
-            if path_exists(&m2_repo) && is_dir(&m2_repo) {
-                if config.should_process_path(&m2_repo) {
-                    caches.push(m2_repo);
-                }
+        // Add custom paths if specified
+        if !config.paths.is_empty() {
+            for path in Self::discover_custom_paths(&project_root, config)? {
+                let kind = registry.kind_for_path(&path);
+                push_unique(
+                    &mut cache_entries,
+                    DiscoveredCache {
+                        path,
+                        kind,
+                        detector: "paths".to_string(),
+                        regenerate: None,
+                        rehydrate: None,
+                        regen_source: None,
+                        project_root: None,
+                        skip_reason: None,
+                        git: None,
+                        project_last_active: None,
+                        project_types: Vec::new(),
+                    },
+                );
             }
         }
 
-        Ok(caches)
-    }
-
-    /// Discover ML/AI caches
-    fn discover_ml_caches(project_root: &Path, config: &MergedConfig) -> Result<Vec<PathBuf>> {
-        let mut caches = Vec::new();
-
-        let ml_cache_patterns = vec![".dvc/cache", ".dvc/tmp", "wandb", ".wandb"];
-
-        for pattern in ml_cache_patterns {
-            let cache_path = project_root.join(pattern);
-            if path_exists(&cache_path) && is_dir(&cache_path) {
-                if config.should_process_path(&cache_path) {
-                    caches.push(cache_path);
-                }
-            }
+        // Ask the repository about every candidate: tracked files are refused
+        // and ignored paths are trusted more
+        for cache in &mut cache_entries {
+            cache.git = git::path_status(&cache.path);
         }
 
-        // Check for global ML caches
-        if let Some(home) = dirs::home_dir() {
-            let ml_caches = vec![
-                home.join(".cache").join("huggingface"),
-                home.join(".cache").join("torch"),
-                home.join(".cache").join("transformers"),
-            ];
-
-            for cache_path in ml_caches {
-                if path_exists(&cache_path) && is_dir(&cache_path) {
-                    if config.should_process_path(&cache_path) {
-                        caches.push(cache_path);
-                    }
-                }
+        // Judge the project by its sources, not by how recently a cache was written
+        let activity = if project_type == ProjectType::Unknown {
+            None
+        } else {
+            let local: Vec<&Path> = cache_entries
+                .iter()
+                .map(|c| c.path.as_path())
+                .filter(|p| p.starts_with(&project_root))
+                .collect();
+            ProjectActivity::detect(&project_root, &local)
+        };
+        let project_types: Vec<String> = registry
+            .matching(&project_root)
+            .iter()
+            .filter(|d| !d.requires_all)
+            .map(|d| d.name.clone())
+            .collect();
+        for cache in &mut cache_entries {
+            if cache.path.starts_with(&project_root) {
+                cache.project_last_active = activity.as_ref().map(|a| a.last_active);
+                cache.project_types = project_types.clone();
             }
         }
 
-        Ok(caches)
+        Ok(Self {
+            project_type,
+            cache_entries,
+            project_root,
+            activity,
+        })
     }
 
-    /// Discover generic caches
-    fn discover_generic_caches(project_root: &Path, config: &MergedConfig) -> Result<Vec<PathBuf>> {
+    /// Every detector's global caches that exist, whatever the project; a
+    /// `max_total_cache` budget counts them all
+    pub fn discover_global(
+        registry: &DetectorRegistry,
+        config: &MergedConfig,
+    ) -> Vec<DiscoveredCache> {
         let mut caches = Vec::new();
-
-        let generic_cache_patterns = vec!["tmp", "temp", ".cache", "cache", ".tmp"];
-
-        for pattern in generic_cache_patterns {
-            let cache_path = project_root.join(pattern);
-            if path_exists(&cache_path) && is_dir(&cache_path) {
-                if config.should_process_path(&cache_path) {
-                    caches.push(cache_path);
+        for detector in registry.detectors() {
+            for path in detector.global.iter().map(|g| g.resolve()) {
+                if path_exists(&path) && is_dir(&path) && config.should_process_path(&path) {
+                    let regen_source = Some(detector.regen_source(&path));
+                    push_unique(
+                        &mut caches,
+                        DiscoveredCache {
+                            path,
+                            kind: detector.kind,
+                            detector: detector.name.clone(),
+                            regenerate: None,
+                            rehydrate: None,
+                            regen_source,
+                            project_root: None,
+                            skip_reason: None,
+                            git: None,
+                            project_last_active: None,
+                            project_types: Vec::new(),
+                        },
+                    );
                 }
             }
         }
-
-        Ok(caches)
+        caches
     }
 
     /// Discover custom paths specified in config
@@ -395,13 +337,11 @@ impl DiscoveryResult {
                 }
 
                 if let Ok(glob_set) = builder.build() {
-                    for entry in walkdir::WalkDir::new(project_root) {
-                        if let Ok(entry) = entry {
-                            if glob_set.is_match(entry.path()) {
-                                if config.should_process_path(entry.path()) {
-                                    caches.push(entry.path().to_path_buf());
-                                }
-                            }
+                    for entry in walkdir::WalkDir::new(project_root).into_iter().flatten() {
+                        if glob_set.is_match(entry.path())
+                            && config.should_process_path(entry.path())
+                        {
+                            caches.push(entry.path().to_path_buf());
                         }
                     }
                 }
@@ -413,10 +353,8 @@ impl DiscoveryResult {
                     project_root.join(pattern)
                 };
 
-                if path_exists(&cache_path) {
-                    if config.should_process_path(&cache_path) {
-                        caches.push(cache_path);
-                    }
+                if path_exists(&cache_path) && config.should_process_path(&cache_path) {
+                    caches.push(cache_path);
                 }
             }
         }
@@ -425,6 +363,30 @@ impl DiscoveryResult {
     }
 }
 
+/// Cache kind selected by a language filter (`None` for auto)
+fn lang_kind(lang: LanguageFilter) -> Option<CacheKind> {
+    match lang {
+        LanguageFilter::Auto => None,
+        LanguageFilter::JavaScript => Some(CacheKind::JavaScript),
+        LanguageFilter::Python => Some(CacheKind::Python),
+        LanguageFilter::Rust => Some(CacheKind::Rust),
+        LanguageFilter::Java => Some(CacheKind::Java),
+        LanguageFilter::MachineLearning => Some(CacheKind::MachineLearning),
+    }
+}
+
+/// Keep the first detector that claims a path (e.g. `build` is both JS and Java),
+/// unless it could not verify the path and a later detector can
+fn push_unique(caches: &mut Vec<DiscoveredCache>, cache: DiscoveredCache) {
+    match caches.iter_mut().find(|c| c.path == cache.path) {
+        Some(existing) if existing.skip_reason.is_some() && cache.skip_reason.is_none() => {
+            *existing = cache;
+        }
+        Some(_) => {}
+        None => caches.push(cache),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -457,6 +419,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bun_and_deno_projects_are_javascript() {
+        for marker in ["bun.lockb", "bun.lock", "deno.json", "deno.jsonc"] {
+            let temp_dir = TempDir::new().unwrap();
+            fs::write(temp_dir.path().join(marker), "").unwrap();
+            assert_eq!(
+                ProjectType::detect(temp_dir.path()).unwrap(),
+                ProjectType::JavaScript,
+                "{} should mark a JavaScript project",
+                marker
+            );
+        }
+    }
+
     #[test]
     fn test_cache_kinds_for_project_type() {
         let js_kinds = ProjectType::JavaScript.get_cache_kinds();
@@ -479,7 +455,6 @@ mod tests {
         fs::create_dir_all("dist").unwrap();
 
         let config = MergedConfig {
-            list: false,
             dry_run: false,
             force: false,
             json: false,
@@ -491,9 +466,18 @@ mod tests {
             backup_dir: ".cachekill-backup".to_string(),
             docker: false,
             npx: false,
-            restore_last: false,
             all: false,
             js_pm: false,
+            npx_packages: vec![],
+            keep_versions: None,
+            gitignored: false,
+            min_size: crate::config::DEFAULT_MIN_SIZE,
+            rules: vec![],
+            explain: false,
+            free_target: None,
+            max_total_cache: None,
+            rehydrate: None,
+            kill_holders: false,
         };
 
         let result = DiscoveryResult::discover(&config).unwrap();
@@ -505,4 +489,36 @@ mod tests {
         );
         assert!(!result.cache_entries.is_empty());
     }
+
+    #[test]
+    fn test_verified_detector_replaces_unverified_claim() {
+        let cache = |detector: &str, kind, skip_reason: Option<&str>| DiscoveredCache {
+            path: PathBuf::from("/work/build"),
+            kind,
+            detector: detector.to_string(),
+            regenerate: None,
+            rehydrate: None,
+            regen_source: None,
+            project_root: None,
+            skip_reason: skip_reason.map(str::to_string),
+            git: None,
+            project_last_active: None,
+            project_types: Vec::new(),
+        };
+
+        let mut caches = Vec::new();
+        push_unique(
+            &mut caches,
+            cache("javascript", CacheKind::JavaScript, Some("not verified")),
+        );
+        push_unique(&mut caches, cache("java", CacheKind::Java, None));
+        push_unique(
+            &mut caches,
+            cache("javascript", CacheKind::JavaScript, Some("not verified")),
+        );
+
+        assert_eq!(caches.len(), 1);
+        assert_eq!(caches[0].detector, "java");
+        assert!(caches[0].skip_reason.is_none());
+    }
 }
@@ -1,5 +1,6 @@
 use crate::cache_entry::{CacheEntry, CacheKind, PlannedAction};
 use crate::config::MergedConfig;
+use crate::regen::{RegenCost, RegenSource};
 use anyhow::{Context, Result};
 use chrono::Utc;
 use std::collections::HashMap;
@@ -140,7 +141,12 @@ impl DockerCacheManager {
                 Utc::now(),
                 false, // Docker images don't have a clear "stale" concept
             )
-            .with_planned_action(self.determine_planned_action());
+            .with_planned_action(self.determine_planned_action())
+            .with_regen_cost(RegenCost::estimate(
+                CacheKind::Docker,
+                RegenSource::Network,
+                system_info.images_size,
+            ));
             entries.push(entry);
         }
 
@@ -179,7 +185,12 @@ impl DockerCacheManager {
                 Utc::now(),
                 false,
             )
-            .with_planned_action(self.determine_planned_action());
+            .with_planned_action(self.determine_planned_action())
+            .with_regen_cost(RegenCost::estimate(
+                CacheKind::Docker,
+                RegenSource::Build,
+                system_info.build_cache_size,
+            ));
             entries.push(entry);
         }
 
@@ -369,7 +380,6 @@ mod tests {
 
     fn create_test_config() -> MergedConfig {
         MergedConfig {
-            list: false,
             dry_run: false,
             force: false,
             json: false,
@@ -381,13 +391,23 @@ mod tests {
             backup_dir: ".cachekill-backup".to_string(),
             docker: false,
             npx: false,
-            restore_last: false,
             all: false,
             js_pm: false,
+            npx_packages: vec![],
+            keep_versions: None,
+            gitignored: false,
+            min_size: crate::config::DEFAULT_MIN_SIZE,
+            rules: vec![],
+            explain: false,
+            free_target: None,
+            max_total_cache: None,
+            rehydrate: None,
+            kill_holders: false,
         }
     }
 
     #[test]
+    #[allow(clippy::overly_complex_bool_expr)]
     fn test_docker_availability() {
         // This test might pass or fail depending on the system
         let available = DockerCacheManager::is_docker_available();
@@ -396,6 +416,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_docker_manager_creation() {
         let config = create_test_config();
         let _manager = DockerCacheManager::new(config);
@@ -8,6 +8,8 @@ use std::process::Command;
 use crate::config::MergedConfig;
 use crate::hf::HfCacheManager;
 use crate::npx::NpxCacheManager;
+use crate::package_managers::traits::Capabilities;
+use crate::package_managers::{PackageManagers, PmLocation};
 use crate::torch::TorchCacheManager;
 
 /// System diagnostic information
@@ -18,6 +20,8 @@ pub struct SystemDiagnostics {
     pub cachekill_version: String,
     pub integrations: IntegrationStatus,
     pub cache_directories: HashMap<String, CacheDirInfo>,
+    pub package_managers: Vec<PmLocation>,
+    pub package_manager_capabilities: Vec<Capabilities>,
     pub environment: EnvironmentInfo,
     pub recommendations: Vec<String>,
 }
@@ -70,6 +74,9 @@ impl SystemDoctor {
 
         let integrations = self.check_integrations();
         let cache_directories = self.check_cache_directories();
+        let pm = PackageManagers::new(self.config.clone());
+        let package_managers = pm.locations();
+        let package_manager_capabilities = pm.capabilities();
         let environment = self.check_environment();
         let recommendations = self.generate_recommendations(&integrations, &cache_directories);
 
@@ -79,6 +86,8 @@ impl SystemDoctor {
             cachekill_version,
             integrations,
             cache_directories,
+            package_managers,
+            package_manager_capabilities,
             environment,
             recommendations,
         })
@@ -213,7 +222,7 @@ impl SystemDoctor {
     /// Check environment
     fn check_environment(&self) -> EnvironmentInfo {
         let home_dir = dirs::home_dir().map(|p| p.to_string_lossy().to_string());
-        let temp_dir = std::env::temp_dir().to_string_lossy().to_string().into();
+        let temp_dir = std::env::temp_dir().to_string_lossy().to_string();
         let cache_dir = dirs::cache_dir().map(|p| p.to_string_lossy().to_string());
 
         let mut env_vars = HashMap::new();
@@ -354,6 +363,39 @@ pub fn handle_doctor(config: &MergedConfig) -> Result<()> {
             }
         }
 
+        if !diagnostics.package_managers.is_empty() {
+            println!("\n📦 Package Manager Caches:");
+            for location in &diagnostics.package_managers {
+                let status = if location.exists { "✅" } else { "❌" };
+                println!(
+                    "  {} {}: {} {} (from {})",
+                    location.manager, location.label, status, location.path, location.source
+                );
+            }
+        }
+
+        if !diagnostics.package_manager_capabilities.is_empty() {
+            println!("\n🧰 Package Manager Capabilities:");
+            for caps in &diagnostics.package_manager_capabilities {
+                let mut modes = Vec::new();
+                if caps.prune {
+                    modes.push("prune");
+                }
+                if caps.delete {
+                    modes.push("delete");
+                }
+                if caps.verify {
+                    modes.push("verify");
+                }
+                let native = caps
+                    .native_command
+                    .as_deref()
+                    .map(|bin| format!("native via {}", bin))
+                    .unwrap_or_else(|| "filesystem only".to_string());
+                println!("  {}: {} ({})", caps.manager, modes.join(", "), native);
+            }
+        }
+
         if !diagnostics.recommendations.is_empty() {
             println!("\n💡 Recommendations:");
             for rec in &diagnostics.recommendations {
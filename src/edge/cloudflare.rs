@@ -19,11 +19,22 @@ pub struct CloudflarePurgeResult {
 /// Cloudflare cache manager
 pub struct CloudflareCacheManager {
     config: MergedConfig,
+    /// Token from `--token`, used instead of CF_API_TOKEN
+    token: Option<String>,
 }
 
 impl CloudflareCacheManager {
     pub fn new(config: MergedConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            token: None,
+        }
+    }
+
+    /// Use `token` instead of the environment variable when given
+    pub fn with_token(mut self, token: Option<&str>) -> Self {
+        self.token = token.map(str::to_string);
+        self
     }
 
     /// Check if Cloudflare CLI (wrangler) is available
@@ -33,13 +44,15 @@ impl CloudflareCacheManager {
 
     /// Check if Cloudflare API token is available
     pub fn token_available(&self) -> bool {
-        env::var("CF_API_TOKEN").is_ok()
+        self.token.is_some() || env::var("CF_API_TOKEN").is_ok()
     }
 
-    /// Get Cloudflare API token from environment
-    #[allow(dead_code)]
+    /// Get the API token: `--token` first, then CF_API_TOKEN
     fn get_token(&self) -> Result<String> {
-        env::var("CF_API_TOKEN").context("CF_API_TOKEN environment variable not set")
+        match &self.token {
+            Some(token) => Ok(token.clone()),
+            None => env::var("CF_API_TOKEN").context("CF_API_TOKEN environment variable not set"),
+        }
     }
 
     /// Purge Cloudflare cache using CLI
@@ -87,7 +100,7 @@ impl CloudflareCacheManager {
             );
             let data = r#"{"purge_everything":true}"#;
 
-            cmd.args(&[
+            cmd.args([
                 "-X",
                 "POST",
                 &url,
@@ -145,8 +158,8 @@ impl CloudflareCacheManager {
             });
         }
 
-        // Try CLI first, then API
-        if self.cli_available() {
+        // Try CLI first, then API; an explicit --token goes straight to the API
+        if self.token.is_none() && self.cli_available() {
             self.purge_via_cli(zone_id)
         } else if self.token_available() {
             self.purge_via_api(zone_id)
@@ -166,11 +179,22 @@ impl CloudflareCacheManager {
         serde_json::json!({
             "cli_available": self.cli_available(),
             "token_available": self.token_available(),
-            "token_source": if self.token_available() { serde_json::Value::String("CF_API_TOKEN".to_string()) } else { serde_json::Value::Null },
+            "token_source": self.token_source(),
             "recommendations": self.get_recommendations()
         })
     }
 
+    /// Where the token comes from, if anywhere
+    fn token_source(&self) -> Option<&'static str> {
+        if self.token.is_some() {
+            Some("--token")
+        } else if env::var("CF_API_TOKEN").is_ok() {
+            Some("CF_API_TOKEN")
+        } else {
+            None
+        }
+    }
+
     /// Get recommendations for setup
     fn get_recommendations(&self) -> Vec<String> {
         let mut recommendations = Vec::new();
@@ -192,8 +216,12 @@ impl CloudflareCacheManager {
 }
 
 /// Handle Cloudflare purge command
-pub fn handle_cloudflare_purge(config: &MergedConfig, zone_id: Option<&str>) -> Result<()> {
-    let manager = CloudflareCacheManager::new(config.clone());
+pub fn handle_cloudflare_purge(
+    config: &MergedConfig,
+    zone_id: Option<&str>,
+    token: Option<&str>,
+) -> Result<()> {
+    let manager = CloudflareCacheManager::new(config.clone()).with_token(token);
 
     let result = manager.purge_cache(zone_id, config.force)?;
 
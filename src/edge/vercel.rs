@@ -19,11 +19,22 @@ pub struct VercelPurgeResult {
 /// Vercel cache manager
 pub struct VercelCacheManager {
     config: MergedConfig,
+    /// Token from `--token`, used instead of VERCEL_TOKEN
+    token: Option<String>,
 }
 
 impl VercelCacheManager {
     pub fn new(config: MergedConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            token: None,
+        }
+    }
+
+    /// Use `token` instead of the environment variable when given
+    pub fn with_token(mut self, token: Option<&str>) -> Self {
+        self.token = token.map(str::to_string);
+        self
     }
 
     /// Check if Vercel CLI is available
@@ -33,13 +44,15 @@ impl VercelCacheManager {
 
     /// Check if Vercel token is available
     pub fn token_available(&self) -> bool {
-        env::var("VERCEL_TOKEN").is_ok()
+        self.token.is_some() || env::var("VERCEL_TOKEN").is_ok()
     }
 
-    /// Get Vercel token from environment
-    #[allow(dead_code)]
+    /// Get the API token: `--token` first, then VERCEL_TOKEN
     fn get_token(&self) -> Result<String> {
-        env::var("VERCEL_TOKEN").context("VERCEL_TOKEN environment variable not set")
+        match &self.token {
+            Some(token) => Ok(token.clone()),
+            None => env::var("VERCEL_TOKEN").context("VERCEL_TOKEN environment variable not set"),
+        }
     }
 
     /// Purge Vercel cache using CLI
@@ -80,7 +93,7 @@ impl VercelCacheManager {
 
         // Use curl as a fallback for API calls
         let mut cmd = Command::new("curl");
-        cmd.args(&[
+        cmd.args([
             "-X",
             "POST",
             "-H",
@@ -144,8 +157,8 @@ impl VercelCacheManager {
             });
         }
 
-        // Try CLI first, then API
-        if self.cli_available() {
+        // Try CLI first, then API; an explicit --token goes straight to the API
+        if self.token.is_none() && self.cli_available() {
             self.purge_via_cli(project_id)
         } else if self.token_available() {
             self.purge_via_api(project_id)
@@ -165,11 +178,22 @@ impl VercelCacheManager {
         serde_json::json!({
             "cli_available": self.cli_available(),
             "token_available": self.token_available(),
-            "token_source": if self.token_available() { serde_json::Value::String("VERCEL_TOKEN".to_string()) } else { serde_json::Value::Null },
+            "token_source": self.token_source(),
             "recommendations": self.get_recommendations()
         })
     }
 
+    /// Where the token comes from, if anywhere
+    fn token_source(&self) -> Option<&'static str> {
+        if self.token.is_some() {
+            Some("--token")
+        } else if env::var("VERCEL_TOKEN").is_ok() {
+            Some("VERCEL_TOKEN")
+        } else {
+            None
+        }
+    }
+
     /// Get recommendations for setup
     fn get_recommendations(&self) -> Vec<String> {
         let mut recommendations = Vec::new();
@@ -191,8 +215,12 @@ impl VercelCacheManager {
 }
 
 /// Handle Vercel purge command
-pub fn handle_vercel_purge(config: &MergedConfig, project_id: Option<&str>) -> Result<()> {
-    let manager = VercelCacheManager::new(config.clone());
+pub fn handle_vercel_purge(
+    config: &MergedConfig,
+    project_id: Option<&str>,
+    token: Option<&str>,
+) -> Result<()> {
+    let manager = VercelCacheManager::new(config.clone()).with_token(token);
 
     let result = manager.purge_cache(project_id, config.force)?;
 
@@ -0,0 +1,311 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::cache_entry::{CacheEntry, PlannedAction};
+use crate::regen::{RegenCost, RegenSource};
+use crate::util;
+
+/// How much space `--free` / `--until-free` asks for
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreeTarget {
+    /// Reclaim this many bytes (`--free 30GB`)
+    Reclaim(u64),
+    /// Stop once the filesystem has this many bytes free (`--until-free 50GB`)
+    FreeBytes(u64),
+    /// Stop once this percentage of the filesystem is free (`--until-free 15%`)
+    FreePercent(f64),
+}
+
+impl FreeTarget {
+    /// Parse an `--until-free` value: a size or a percentage
+    pub fn parse_until(value: &str) -> Result<Self> {
+        match value.trim().strip_suffix('%') {
+            Some(percent) => {
+                let percent = f64::from_str(percent.trim())
+                    .with_context(|| format!("Invalid percentage: {}", value))?;
+                if !(0.0..=100.0).contains(&percent) {
+                    anyhow::bail!("Percentage must be between 0 and 100: {}", value);
+                }
+                Ok(Self::FreePercent(percent))
+            }
+            None => Ok(Self::FreeBytes(util::parse_size(value)?)),
+        }
+    }
+
+    /// Bytes that must be freed on `disk` to meet the target
+    pub fn needed_bytes(&self, disk: &DiskSpace) -> u64 {
+        match *self {
+            Self::Reclaim(bytes) => bytes,
+            Self::FreeBytes(bytes) => bytes.saturating_sub(disk.available_bytes),
+            Self::FreePercent(percent) => {
+                let wanted = (disk.total_bytes as f64 * percent / 100.0).ceil() as u64;
+                wanted.saturating_sub(disk.available_bytes)
+            }
+        }
+    }
+
+    /// The target as the user wrote it
+    pub fn describe(&self) -> String {
+        match *self {
+            Self::Reclaim(bytes) => format!("free {}", format_size(bytes)),
+            Self::FreeBytes(bytes) => format!("until {} free", format_size(bytes)),
+            Self::FreePercent(percent) => format!("until {}% free", percent),
+        }
+    }
+}
+
+/// Size and free space of the filesystem holding a path
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSpace {
+    pub mount_point: PathBuf,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+impl DiskSpace {
+    /// The filesystem `path` lives on: the disk with the longest matching mount point
+    pub fn of(path: &Path) -> Result<Self> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve {}", path.display()))?;
+        let disks = sysinfo::Disks::new_with_refreshed_list();
+        disks
+            .list()
+            .iter()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .map(|disk| Self {
+                mount_point: disk.mount_point().to_path_buf(),
+                total_bytes: disk.total_space(),
+                available_bytes: disk.available_space(),
+            })
+            .with_context(|| format!("No filesystem found for {}", path.display()))
+    }
+
+    /// Whether `path` is on this filesystem; paths that cannot be resolved are not
+    fn contains(&self, path: &Path, mount_points: &[PathBuf]) -> bool {
+        let Ok(path) = path.canonicalize() else {
+            return false;
+        };
+        mount_points
+            .iter()
+            .filter(|mount| path.starts_with(mount))
+            .max_by_key(|mount| mount.as_os_str().len())
+            .is_some_and(|mount| *mount == self.mount_point)
+    }
+}
+
+/// One entry in the order it would be cleaned
+#[derive(Debug, Clone, Serialize)]
+pub struct FreeStep {
+    pub entry: CacheEntry,
+    pub freed_bytes: u64,
+    /// Bytes freed once this entry and every one before it are cleaned
+    pub cumulative_bytes: u64,
+    pub score: f64,
+}
+
+/// The entries a free-space target cleans, in order, and where it stops
+#[derive(Debug, Clone, Serialize)]
+pub struct FreePlan {
+    pub target: FreeTarget,
+    pub disk: DiskSpace,
+    pub needed_bytes: u64,
+    /// Entries cleaned, best score first, stopping as soon as the target is met
+    pub steps: Vec<FreeStep>,
+    /// Ranked entries the target does not need
+    pub not_needed: Vec<FreeStep>,
+    /// Entries that would only move to the backup directory on the same filesystem
+    pub backups_ignored: usize,
+    /// Entries on another filesystem
+    pub elsewhere: usize,
+    pub freed_bytes: u64,
+    pub met: bool,
+}
+
+impl FreePlan {
+    /// Rank `entries` and take them in order until `target` is met on `disk`
+    pub fn new(entries: &[CacheEntry], target: FreeTarget, disk: DiskSpace) -> Self {
+        let mount_points: Vec<PathBuf> = sysinfo::Disks::new_with_refreshed_list()
+            .list()
+            .iter()
+            .map(|d| d.mount_point().to_path_buf())
+            .collect();
+        Self::rank(entries, target, disk, |disk, path| {
+            disk.contains(path, &mount_points)
+        })
+    }
+
+    fn rank(
+        entries: &[CacheEntry],
+        target: FreeTarget,
+        disk: DiskSpace,
+        on_disk: impl Fn(&DiskSpace, &Path) -> bool,
+    ) -> Self {
+        let needed_bytes = target.needed_bytes(&disk);
+        let mut backups_ignored = 0;
+        let mut elsewhere = 0;
+
+        let mut ranked: Vec<FreeStep> = Vec::new();
+        for entry in entries {
+            match entry.planned_action {
+                Some(PlannedAction::Delete) | Some(PlannedAction::Prune) => {}
+                // The backup directory sits in the project, so backing up frees nothing
+                Some(PlannedAction::Backup) => {
+                    backups_ignored += 1;
+                    continue;
+                }
+                Some(PlannedAction::Skip) | None => continue,
+            }
+            if !on_disk(&disk, &entry.path) {
+                elsewhere += 1;
+                continue;
+            }
+            let freed_bytes = entry.freed_bytes();
+            if freed_bytes == 0 {
+                continue;
+            }
+            ranked.push(FreeStep {
+                entry: entry.clone(),
+                freed_bytes,
+                cumulative_bytes: 0,
+                score: score(entry, freed_bytes),
+            });
+        }
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        let mut freed_bytes = 0;
+        let mut steps = Vec::new();
+        let mut not_needed = Vec::new();
+        for mut step in ranked {
+            if freed_bytes >= needed_bytes {
+                not_needed.push(step);
+                continue;
+            }
+            freed_bytes += step.freed_bytes;
+            step.cumulative_bytes = freed_bytes;
+            steps.push(step);
+        }
+
+        Self {
+            target,
+            disk,
+            needed_bytes,
+            steps,
+            not_needed,
+            backups_ignored,
+            elsewhere,
+            freed_bytes,
+            met: freed_bytes >= needed_bytes,
+        }
+    }
+
+    /// The entries to clean, in order
+    pub fn entries(&self) -> Vec<CacheEntry> {
+        self.steps.iter().map(|step| step.entry.clone()).collect()
+    }
+}
+
+/// Higher is better to clean first: stale, large and cheap to regenerate
+fn score(entry: &CacheEntry, freed_bytes: u64) -> f64 {
+    let age_days = (Utc::now() - entry.last_used).num_days().max(0) as f64;
+    let staleness = 1.0 + age_days / 30.0 + if entry.stale { 1.0 } else { 0.0 };
+    let size = (1.0 + freed_bytes as f64 / 1_000_000.0).log2();
+    let cost = entry
+        .regen_cost
+        .unwrap_or_else(|| RegenCost::estimate(entry.kind, RegenSource::Build, freed_bytes));
+    // Minutes to get the cache back, so a quick rebuild barely counts
+    staleness * size / (1.0 + cost.seconds() / 60.0)
+}
+
+fn format_size(bytes: u64) -> String {
+    humansize::format_size(bytes, humansize::DECIMAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache_entry::CacheKind;
+    use chrono::Duration;
+
+    fn entry(path: &str, kind: CacheKind, size: u64, age_days: i64) -> CacheEntry {
+        let source = if kind == CacheKind::Rust {
+            RegenSource::Build
+        } else {
+            RegenSource::Network
+        };
+        CacheEntry::new(
+            PathBuf::from(path),
+            kind,
+            size,
+            Utc::now() - Duration::days(age_days),
+            age_days > 14,
+        )
+        .with_planned_action(PlannedAction::Delete)
+        .with_regen_cost(RegenCost::estimate(kind, source, size))
+    }
+
+    fn disk(available: u64) -> DiskSpace {
+        DiskSpace {
+            mount_point: PathBuf::from("/"),
+            total_bytes: 1_000_000_000,
+            available_bytes: available,
+        }
+    }
+
+    #[test]
+    fn test_parse_until_free() {
+        assert_eq!(
+            FreeTarget::parse_until("15%").unwrap(),
+            FreeTarget::FreePercent(15.0)
+        );
+        assert_eq!(
+            FreeTarget::parse_until("2GB").unwrap(),
+            FreeTarget::FreeBytes(2_000_000_000)
+        );
+        assert!(FreeTarget::parse_until("150%").is_err());
+        assert_eq!(
+            FreeTarget::FreePercent(15.0).needed_bytes(&disk(100_000_000)),
+            50_000_000
+        );
+        assert_eq!(FreeTarget::FreeBytes(10).needed_bytes(&disk(100)), 0);
+    }
+
+    #[test]
+    fn test_plan_stops_once_target_is_met() {
+        let entries = vec![
+            entry("/a/model", CacheKind::MachineLearning, 300_000_000, 1),
+            entry("/a/target", CacheKind::Rust, 200_000_000, 60),
+            entry("/a/node_modules", CacheKind::JavaScript, 100_000_000, 40),
+            entry("/b/node_modules", CacheKind::JavaScript, 50_000_000, 2)
+                .with_planned_action(PlannedAction::Backup),
+        ];
+        let plan = FreePlan::rank(
+            &entries,
+            FreeTarget::Reclaim(250_000_000),
+            disk(0),
+            |_, _| true,
+        );
+
+        let order: Vec<_> = plan
+            .steps
+            .iter()
+            .map(|s| s.entry.path.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(order, vec!["/a/target", "/a/node_modules"]);
+        assert_eq!(plan.freed_bytes, 300_000_000);
+        assert!(plan.met);
+        assert_eq!(plan.not_needed.len(), 1);
+        assert_eq!(plan.backups_ignored, 1);
+
+        let short = FreePlan::rank(&entries, FreeTarget::Reclaim(u64::MAX), disk(0), |_, _| {
+            true
+        });
+        assert!(!short.met);
+        assert_eq!(short.steps.len(), 3);
+    }
+}
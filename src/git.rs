@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// What the enclosing git repository knows about a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathStatus {
+    /// Tracked files at or below the path
+    pub tracked_files: usize,
+    /// Whether the path is matched by a `.gitignore` rule
+    pub ignored: bool,
+}
+
+/// Ask git about `path`, or `None` when git is not installed or `path` is
+/// not inside a work tree
+pub fn path_status(path: &Path) -> Option<PathStatus> {
+    let parent = path.parent()?;
+    let name = path.file_name()?.to_string_lossy();
+    // A trailing slash lets directory-only patterns such as `build/` match
+    let target = if path.is_dir() {
+        format!("{}/", name)
+    } else {
+        name.to_string()
+    };
+
+    let tracked = git(parent, &["ls-files", "-z", "--", &target])?;
+    if !tracked.status.success() {
+        return None;
+    }
+    let ignored = git(parent, &["check-ignore", "-q", "--", &target])
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+    Some(PathStatus {
+        tracked_files: split_nul(&tracked.stdout).count(),
+        ignored,
+    })
+}
+
+/// Ignored directories in the work tree at `root`, collapsed to their topmost
+/// ignored ancestor. Empty when `root` is not a repository.
+pub fn ignored_dirs(root: &Path) -> Vec<PathBuf> {
+    let Some(output) = git(
+        root,
+        &[
+            "ls-files",
+            "-z",
+            "--others",
+            "--ignored",
+            "--exclude-standard",
+            "--directory",
+        ],
+    ) else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    split_nul(&output.stdout)
+        .map(|name| String::from_utf8_lossy(name).to_string())
+        .filter_map(|name| name.strip_suffix('/').map(|dir| root.join(dir)))
+        .collect()
+}
+
+/// Time of the newest commit touching `dir`
+pub fn last_commit_time(dir: &Path) -> Option<DateTime<Utc>> {
+    let output = git(dir, &["log", "-1", "--format=%ct", "--", "."])?;
+    parse_timestamp(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Time of the newest HEAD reflog entry (checkouts, commits, rebases), only
+/// when `dir` is the top of its work tree: the reflog covers the whole repository
+pub fn last_reflog_time(dir: &Path) -> Option<DateTime<Utc>> {
+    let toplevel = git(dir, &["rev-parse", "--show-toplevel"])?;
+    let toplevel = PathBuf::from(String::from_utf8_lossy(&toplevel.stdout).trim());
+    if toplevel.canonicalize().ok()? != dir.canonicalize().ok()? {
+        return None;
+    }
+    let output = git(dir, &["reflog", "-1", "--date=unix", "--format=%gd"])?;
+    // e.g. HEAD@{1700000000}
+    let selector = String::from_utf8_lossy(&output.stdout);
+    let start = selector.find('{')? + 1;
+    let end = selector.rfind('}')?;
+    parse_timestamp(selector.get(start..end)?)
+}
+
+/// Tracked files under `dir`, or `None` outside a work tree
+pub fn tracked_files(dir: &Path) -> Option<Vec<PathBuf>> {
+    let output = git(dir, &["ls-files", "-z"])?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        split_nul(&output.stdout)
+            .map(|name| dir.join(String::from_utf8_lossy(name).as_ref()))
+            .collect(),
+    )
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(value.trim().parse().ok()?, 0)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<std::process::Output> {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()
+}
+
+fn split_nul(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    bytes
+        .split(|byte| *byte == 0)
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn init_repo(root: &Path) -> bool {
+        Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["init", "-q"])
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn test_path_status_and_ignored_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        if !init_repo(root) {
+            return;
+        }
+        fs::write(root.join(".gitignore"), "dist/\n").unwrap();
+        fs::create_dir_all(root.join("dist/assets")).unwrap();
+        fs::write(root.join("dist/assets/app.js"), "x").unwrap();
+        fs::create_dir_all(root.join("scripts")).unwrap();
+        fs::write(root.join("scripts/release.sh"), "echo").unwrap();
+        git(root, &["add", "scripts/release.sh"]).unwrap();
+
+        let dist = path_status(&root.join("dist")).unwrap();
+        assert_eq!(
+            dist,
+            PathStatus {
+                tracked_files: 0,
+                ignored: true
+            }
+        );
+        let scripts = path_status(&root.join("scripts")).unwrap();
+        assert_eq!(scripts.tracked_files, 1);
+        assert!(!scripts.ignored);
+
+        assert_eq!(ignored_dirs(root), vec![root.join("dist")]);
+    }
+
+    #[test]
+    fn test_outside_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(path_status(temp_dir.path()), None);
+        assert!(ignored_dirs(temp_dir.path()).is_empty());
+    }
+}
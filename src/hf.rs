@@ -89,7 +89,7 @@ impl HfCacheManager {
         }
 
         // Sort by size descending
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         Ok(entries)
     }
@@ -118,11 +118,11 @@ impl HfCacheManager {
 
         // Get top repos and models
         let mut top_repos: Vec<(String, u64)> = repo_sizes.clone().into_iter().collect();
-        top_repos.sort_by(|a, b| b.1.cmp(&a.1));
+        top_repos.sort_by_key(|r| std::cmp::Reverse(r.1));
         top_repos.truncate(10);
 
         let mut top_models: Vec<(String, u64)> = model_sizes.clone().into_iter().collect();
-        top_models.sort_by(|a, b| b.1.cmp(&a.1));
+        top_models.sort_by_key(|m| std::cmp::Reverse(m.1));
         top_models.truncate(10);
 
         Ok(HfStats {
@@ -168,14 +168,16 @@ impl HfCacheManager {
                 PlannedAction::Skip
             };
 
-            cache_entries.push(CacheEntry {
-                path: entry.path,
-                kind: CacheKind::MachineLearning,
-                size_bytes: entry.size_bytes,
-                last_used: entry.last_used,
-                stale: is_stale,
-                planned_action: Some(planned_action),
-            });
+            cache_entries.push(
+                CacheEntry::new(
+                    entry.path,
+                    CacheKind::MachineLearning,
+                    entry.size_bytes,
+                    entry.last_used,
+                    is_stale,
+                )
+                .with_planned_action(planned_action),
+            );
         }
 
         Ok(cache_entries)
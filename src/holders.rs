@@ -0,0 +1,195 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, Signal, System, UpdateKind};
+
+/// How long a stopped process gets to exit before it is killed
+const STOP_GRACE: Duration = Duration::from_secs(5);
+
+/// A running process working in or holding files open inside a cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProcessHolder {
+    pub pid: u32,
+    pub name: String,
+}
+
+impl fmt::Display for ProcessHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pid {} ({})", self.pid, self.name)
+    }
+}
+
+/// Working directories and open files of every visible process, taken once per run
+pub struct ProcessScan {
+    processes: Vec<(ProcessHolder, Vec<PathBuf>)>,
+}
+
+impl ProcessScan {
+    /// Snapshot the processes running now, except this one
+    pub fn new() -> Self {
+        let mut system = System::new();
+        system.refresh_processes_specifics(ProcessRefreshKind::new().with_cwd(UpdateKind::Always));
+        let own_pid = std::process::id();
+
+        let processes = system
+            .processes()
+            .iter()
+            .filter(|(pid, _)| pid.as_u32() != own_pid)
+            .map(|(pid, process)| {
+                let mut paths = open_paths(pid.as_u32());
+                if let Some(cwd) = process.cwd() {
+                    paths.push(cwd.to_path_buf());
+                }
+                let holder = ProcessHolder {
+                    pid: pid.as_u32(),
+                    name: process.name().to_string(),
+                };
+                (holder, paths)
+            })
+            .collect();
+        Self { processes }
+    }
+
+    /// Build a scan from known processes and the paths they use
+    #[cfg(test)]
+    pub fn from_processes(processes: Vec<(ProcessHolder, Vec<PathBuf>)>) -> Self {
+        Self { processes }
+    }
+
+    /// Processes whose working directory or an open file lies inside `path`
+    pub fn holders_of(&self, path: &Path) -> Vec<ProcessHolder> {
+        // /proc reports resolved paths, so compare against the resolved cache path
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut holders = self
+            .processes
+            .iter()
+            .filter(|(_, paths)| paths.iter().any(|p| p.starts_with(&path)))
+            .map(|(holder, _)| holder.clone())
+            .collect::<Vec<_>>();
+        holders.sort_by_key(|h| h.pid);
+        holders
+    }
+}
+
+/// Working directory and open files from `/proc/<pid>`; empty where that is unreadable
+#[cfg(target_os = "linux")]
+fn open_paths(pid: u32) -> Vec<PathBuf> {
+    let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+    let mut paths: Vec<PathBuf> = std::fs::read_link(proc_dir.join("cwd"))
+        .into_iter()
+        .collect();
+    if let Ok(fds) = std::fs::read_dir(proc_dir.join("fd")) {
+        paths.extend(
+            fds.flatten()
+                .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+                .filter(|target| target.is_absolute()),
+        );
+    }
+    paths
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_paths(_pid: u32) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// Ask `holder` to exit, and kill it if it is still running after a grace period
+pub fn stop(holder: &ProcessHolder) -> Result<()> {
+    let pid = Pid::from_u32(holder.pid);
+    let mut system = System::new();
+    if !running(&mut system, pid) {
+        return Ok(());
+    }
+    let sent = system.process(pid).and_then(|process| {
+        process
+            .kill_with(Signal::Term)
+            .or_else(|| Some(process.kill()))
+    });
+    if sent != Some(true) {
+        anyhow::bail!("could not signal {}", holder);
+    }
+
+    let started = Instant::now();
+    while started.elapsed() < STOP_GRACE {
+        if !running(&mut system, pid) {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    if let Some(process) = system.process(pid) {
+        process.kill();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    if running(&mut system, pid) {
+        anyhow::bail!("{} is still running", holder);
+    }
+    Ok(())
+}
+
+/// Whether `pid` still runs; an exited process waiting to be reaped holds no files
+fn running(system: &mut System, pid: Pid) -> bool {
+    system.refresh_process_specifics(pid, ProcessRefreshKind::new())
+        && system
+            .process(pid)
+            .is_some_and(|process| process.status() != ProcessStatus::Zombie)
+}
+
+/// `in use by pid 4312 (cargo)`, naming how many others there are
+pub fn in_use_reason(holders: &[ProcessHolder]) -> String {
+    match holders {
+        [] => "in use".to_string(),
+        [holder] => format!("in use by {}", holder),
+        [holder, rest @ ..] => format!("in use by {} and {} more", holder, rest.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_holders_of_matches_cwd_and_open_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        std::fs::create_dir_all(target.join("debug")).unwrap();
+        let target = target.canonicalize().unwrap();
+        let cargo = ProcessHolder {
+            pid: 4312,
+            name: "cargo".to_string(),
+        };
+        let editor = ProcessHolder {
+            pid: 77,
+            name: "vim".to_string(),
+        };
+        let scan = ProcessScan::from_processes(vec![
+            (cargo.clone(), vec![target.join("debug/build.lock")]),
+            (editor, vec![temp_dir.path().join("src/main.rs")]),
+        ]);
+
+        assert_eq!(scan.holders_of(&target), vec![cargo.clone()]);
+        assert!(scan
+            .holders_of(&temp_dir.path().join("node_modules"))
+            .is_empty());
+        assert_eq!(in_use_reason(&[cargo]), "in use by pid 4312 (cargo)");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_scan_sees_child_working_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .current_dir(temp_dir.path())
+            .spawn()
+            .unwrap();
+
+        let holders = ProcessScan::new().holders_of(temp_dir.path());
+        let found = holders.iter().find(|h| h.pid == child.id()).cloned();
+        let _ = child.kill();
+        let _ = child.wait();
+        assert_eq!(found.map(|h| h.name), Some("sleep".to_string()));
+    }
+}
@@ -0,0 +1,342 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::MergedConfig;
+use crate::discover::{DiscoveryResult, ProjectType};
+use crate::util::get_current_dir;
+
+/// File written by `init`
+const CONFIG_FILE: &str = ".cachekillrc";
+
+/// Lockfiles and manifests that name the package manager in use
+const MANAGER_MARKERS: &[(&str, &str)] = &[
+    ("package-lock.json", "npm"),
+    ("npm-shrinkwrap.json", "npm"),
+    ("yarn.lock", "yarn"),
+    ("pnpm-lock.yaml", "pnpm"),
+    ("bun.lockb", "bun"),
+    ("bun.lock", "bun"),
+    ("deno.lock", "deno"),
+    ("deno.json", "deno"),
+    ("poetry.lock", "poetry"),
+    ("uv.lock", "uv"),
+    ("Pipfile.lock", "pipenv"),
+    ("Cargo.lock", "cargo"),
+    ("gradlew", "gradle"),
+    ("mvnw", "maven"),
+];
+
+/// JS package managers whose global caches `--js-pm` can clean
+const JS_MANAGERS: &[&str] = &["npm", "yarn", "pnpm", "bun", "deno"];
+
+/// Virtualenv directories worth protecting when the interpreter is pinned
+const VENV_DIRS: &[&str] = &[".venv", "venv"];
+
+/// A suggested exclude pattern and why it was suggested
+#[derive(Debug, Clone, Serialize)]
+pub struct SuggestedExclude {
+    pub pattern: String,
+    pub reason: String,
+}
+
+/// What `init` learned about the project, and the settings it proposes
+#[derive(Debug, Clone, Serialize)]
+pub struct InitPlan {
+    pub project_type: String,
+    /// Monorepo layout (`npm workspaces`, `cargo workspace`, ...), if any
+    pub workspace: Option<String>,
+    pub managers: Vec<String>,
+    /// Caches present right now, relative to the project root
+    pub caches: Vec<String>,
+    pub excludes: Vec<SuggestedExclude>,
+    pub stale_days: u32,
+    pub safe_delete: bool,
+}
+
+impl InitPlan {
+    /// Inspect `root` and propose settings for it
+    pub fn detect(root: &Path) -> Result<Self> {
+        let project_type = ProjectType::detect(root)?;
+        let discovery = DiscoveryResult::discover_in(
+            root.to_path_buf(),
+            &crate::detectors::DetectorRegistry::load(),
+            &MergedConfig::default(),
+        )?;
+
+        let mut managers: Vec<String> = Vec::new();
+        for (marker, manager) in MANAGER_MARKERS {
+            if root.join(marker).exists() && !managers.iter().any(|m| m == manager) {
+                managers.push(manager.to_string());
+            }
+        }
+
+        let local: Vec<_> = discovery
+            .cache_entries
+            .iter()
+            .filter(|c| c.path.starts_with(root))
+            .collect();
+        let caches = local
+            .iter()
+            .map(|c| relative(root, &c.path))
+            .collect::<Vec<_>>();
+
+        let mut excludes = Vec::new();
+        if pinned_interpreter(root) {
+            for dir in VENV_DIRS {
+                if root.join(dir).is_dir() {
+                    excludes.push(SuggestedExclude {
+                        pattern: format!("**/{}", dir),
+                        reason: "pinned interpreter; slow to rebuild".to_string(),
+                    });
+                }
+            }
+        }
+        // Caches discovery refused are kept out explicitly, so a later
+        // detector change cannot start proposing them
+        for cache in local.iter().filter(|c| c.skip_reason.is_some()) {
+            excludes.push(SuggestedExclude {
+                pattern: format!("**/{}", relative(root, &cache.path)),
+                reason: cache.skip_reason.clone().unwrap_or_default(),
+            });
+        }
+
+        Ok(Self {
+            stale_days: suggested_stale_days(&project_type),
+            project_type: format!("{:?}", project_type).to_lowercase(),
+            workspace: detect_workspace(root),
+            managers,
+            caches,
+            excludes,
+            safe_delete: true,
+        })
+    }
+
+    /// The commented `.cachekillrc` for this plan
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# cachekill configuration, generated by `cachekill init`\n");
+        out.push_str(&format!("# Project type: {}\n", self.project_type));
+        if let Some(workspace) = &self.workspace {
+            out.push_str(&format!("# Workspace: {}\n", workspace));
+        }
+        if !self.managers.is_empty() {
+            out.push_str(&format!(
+                "# Package managers: {}\n",
+                self.managers.join(", ")
+            ));
+        }
+        if !self.caches.is_empty() {
+            out.push_str("# Caches found:\n");
+            for cache in &self.caches {
+                out.push_str(&format!("#   {}\n", cache));
+            }
+        }
+        out.push_str("# Run `cachekill config check` after editing.\n\n");
+
+        out.push_str("# Caches untouched for this many days are marked stale\n");
+        out.push_str(&format!("stale_days = {}\n\n", self.stale_days));
+        out.push_str("# Move caches to a backup directory instead of deleting them\n");
+        out.push_str(&format!("safe_delete = {}\n\n", self.safe_delete));
+
+        out.push_str("# Added to the built-in excludes (.git, .cachekill-backup, ...)\n");
+        if self.excludes.is_empty() {
+            out.push_str("exclude_paths = []\n");
+        } else {
+            out.push_str("exclude_paths = [\n");
+            for exclude in &self.excludes {
+                out.push_str(&format!(
+                    "    {}, # {}\n",
+                    toml::Value::String(exclude.pattern.clone()),
+                    exclude.reason
+                ));
+            }
+            out.push_str("]\n");
+        }
+
+        let js_managers: Vec<&str> = self
+            .managers
+            .iter()
+            .map(String::as_str)
+            .filter(|m| JS_MANAGERS.contains(m))
+            .collect();
+        if !js_managers.is_empty() {
+            out.push_str(&format!(
+                "\n# Also clean the global caches of {} (same as --js-pm)\n",
+                js_managers.join(", ")
+            ));
+            out.push_str("# include_js_pm = true\n");
+        }
+
+        out.push_str("\n# Presets selected with --profile, e.g. `cachekill --profile deep`\n");
+        out.push_str("# [profiles.deep]\n# stale_days = 0\n# include_docker = true\n");
+        out
+    }
+}
+
+/// Handle `init`: write a `.cachekillrc` tailored to the current project.
+/// `interactive` asks about each suggestion; `dry_run` prints the file instead.
+pub fn handle_init(force: bool, interactive: bool, dry_run: bool, json: bool) -> Result<()> {
+    let root = get_current_dir()?;
+    let path = root.join(CONFIG_FILE);
+    if path.exists() && !force && !dry_run {
+        anyhow::bail!(
+            "{} already exists; use --force to overwrite it",
+            path.display()
+        );
+    }
+
+    let mut plan = InitPlan::detect(&root)?;
+    if interactive {
+        prompt(&mut plan)?;
+    }
+    let content = plan.render();
+
+    if !dry_run {
+        fs::write(&path, &content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    if json {
+        let report = serde_json::json!({
+            "path": path,
+            "written": !dry_run,
+            "plan": plan,
+            "content": content,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if dry_run {
+        print!("{}", content);
+    } else {
+        println!("✅ Wrote {}", path.display());
+        println!("  Project type: {}", plan.project_type);
+        if !plan.managers.is_empty() {
+            println!("  Package managers: {}", plan.managers.join(", "));
+        }
+        println!("  Excludes: {}", plan.excludes.len());
+    }
+    Ok(())
+}
+
+/// Let the user adjust the suggestions
+fn prompt(plan: &mut InitPlan) -> Result<()> {
+    plan.stale_days = inquire::CustomType::<u32>::new("Mark caches stale after how many days?")
+        .with_default(plan.stale_days)
+        .prompt()?;
+    plan.safe_delete = inquire::Confirm::new("Back up caches instead of deleting them?")
+        .with_default(plan.safe_delete)
+        .prompt()?;
+
+    if !plan.excludes.is_empty() {
+        let options: Vec<String> = plan
+            .excludes
+            .iter()
+            .map(|e| format!("{} ({})", e.pattern, e.reason))
+            .collect();
+        let defaults: Vec<usize> = (0..options.len()).collect();
+        let chosen = inquire::MultiSelect::new("Paths to never clean:", options.clone())
+            .with_default(&defaults)
+            .prompt()?;
+        plan.excludes = plan
+            .excludes
+            .iter()
+            .zip(&options)
+            .filter(|(_, option)| chosen.contains(option))
+            .map(|(exclude, _)| exclude.clone())
+            .collect();
+    }
+    Ok(())
+}
+
+/// Stale threshold suited to how expensive the project's caches are to rebuild
+fn suggested_stale_days(project_type: &ProjectType) -> u32 {
+    match project_type {
+        ProjectType::MachineLearning => 30,
+        ProjectType::Rust | ProjectType::Java | ProjectType::Mixed => 21,
+        _ => 14,
+    }
+}
+
+/// Monorepo layout declared at `root`, if any
+fn detect_workspace(root: &Path) -> Option<String> {
+    let read = |name: &str| fs::read_to_string(root.join(name)).ok();
+
+    if root.join("pnpm-workspace.yaml").exists() {
+        return Some("pnpm workspace".to_string());
+    }
+    if let Some(package) = read("package.json")
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+    {
+        if package.get("workspaces").is_some() {
+            return Some("npm/yarn workspaces".to_string());
+        }
+    }
+    if read("Cargo.toml").is_some_and(|content| content.lines().any(|l| l.trim() == "[workspace]"))
+    {
+        return Some("cargo workspace".to_string());
+    }
+    if ["settings.gradle", "settings.gradle.kts"]
+        .iter()
+        .filter_map(|name| read(name))
+        .any(|content| content.contains("include"))
+    {
+        return Some("gradle multi-project".to_string());
+    }
+    None
+}
+
+/// Whether the Python interpreter or dependencies are pinned, making the virtualenv costly
+fn pinned_interpreter(root: &Path) -> bool {
+    if root.join(".python-version").exists() || root.join("runtime.txt").exists() {
+        return true;
+    }
+    fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with("requirements") && name.ends_with(".txt")
+        })
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .any(|content| content.lines().any(|line| line.contains("==")))
+}
+
+/// `path` relative to `root`, for patterns in the generated file
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_workspace_and_pinned_venv() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"a\"]\n").unwrap();
+        assert_eq!(detect_workspace(root).as_deref(), Some("cargo workspace"));
+
+        fs::create_dir_all(root.join(".venv")).unwrap();
+        fs::write(root.join("requirements.txt"), "requests>=2\n").unwrap();
+        assert!(!pinned_interpreter(root));
+        fs::write(root.join("requirements-dev.txt"), "pytest==8.0.0\n").unwrap();
+        assert!(pinned_interpreter(root));
+
+        let plan = InitPlan::detect(root).unwrap();
+        assert_eq!(plan.excludes.len(), 1);
+        assert_eq!(plan.excludes[0].pattern, "**/.venv");
+        assert!(plan.managers.is_empty());
+
+        let rendered: toml::Value = toml::from_str(&plan.render()).unwrap();
+        assert_eq!(rendered["exclude_paths"][0].as_str(), Some("**/.venv"));
+        assert_eq!(rendered["stale_days"].as_integer(), Some(21));
+    }
+}
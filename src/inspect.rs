@@ -1,5 +1,8 @@
-use crate::cache_entry::{CacheEntry, CacheKind, PlannedAction};
+use crate::cache_entry::{CacheEntry, CacheKind, Confidence, PlannedAction};
 use crate::config::MergedConfig;
+use crate::discover::DiscoveredCache;
+use crate::regen::{RegenCost, RegenSource};
+use crate::rules::RuleSet;
 use crate::util::{get_most_recent_mtime, get_size, is_dir, path_exists};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
@@ -18,98 +21,78 @@ impl CacheInspector {
     }
 
     /// Inspect a list of cache paths and return cache entries
-    pub fn inspect_caches(&self, cache_paths: &[std::path::PathBuf]) -> Result<Vec<CacheEntry>> {
-        let entries: Result<Vec<_>> = cache_paths
+    pub fn inspect_caches(&self, caches: &[DiscoveredCache]) -> Result<Vec<CacheEntry>> {
+        let mut entries = caches
             .par_iter()
-            .map(|path| self.inspect_single_cache(path))
-            .collect();
+            .map(|cache| self.inspect_single_cache(cache))
+            .collect::<Result<Vec<_>>>()?;
+
+        // `--gitignored` proposes large ignored directories, biggest first
+        if self.config.gitignored {
+            entries.retain(|e| e.size_bytes >= self.config.min_size);
+            entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
+        }
 
-        entries
+        Ok(entries)
     }
 
     /// Inspect a single cache path
-    fn inspect_single_cache(&self, path: &Path) -> Result<CacheEntry> {
+    fn inspect_single_cache(&self, cache: &DiscoveredCache) -> Result<CacheEntry> {
+        let path = cache.path.as_path();
         if !path_exists(path) {
             return Err(anyhow::anyhow!("Path does not exist: {}", path.display()));
         }
 
-        let kind = self.determine_cache_kind(path);
+        // The kind was recorded by the detector that found the path
+        let kind = cache.kind;
         let size_bytes = self.calculate_size(path)?;
         let last_used = self.get_last_used_time(path)?;
-        let stale = self.is_stale(&last_used);
-
-        let mut entry = CacheEntry::new(path.to_path_buf(), kind, size_bytes, last_used, stale);
+        // A fresh cache in an abandoned project is stale too
+        let stale = self.is_stale(&last_used)
+            || cache
+                .project_last_active
+                .is_some_and(|active| self.is_stale(&active));
+
+        // Paths the repository ignores are the safest to remove
+        let confidence = cache.git.map(|status| {
+            if status.ignored {
+                Confidence::High
+            } else {
+                Confidence::Medium
+            }
+        });
+        let mut entry = CacheEntry::new(path.to_path_buf(), kind, size_bytes, last_used, stale)
+            .with_regenerate(cache.regenerate.clone())
+            .with_rehydrate(cache.rehydrate.clone())
+            .with_regen_cost(RegenCost::estimate(
+                kind,
+                cache.regen_source.unwrap_or(RegenSource::Build),
+                size_bytes,
+            ))
+            .with_skip_reason(cache.skip_reason.clone())
+            .with_confidence(confidence)
+            .with_project_last_active(cache.project_last_active)
+            .with_detector(&cache.detector);
+        entry.project_types = cache.project_types.clone();
+        entry.project_root = cache.project_root.clone();
+
+        // Never propose a path holding files the repository tracks
+        if let Some(status) = cache.git.filter(|status| status.tracked_files > 0) {
+            entry.skip_reason = Some(format!(
+                "contains {} git-tracked file{}",
+                status.tracked_files,
+                if status.tracked_files == 1 { "" } else { "s" }
+            ));
+        }
 
         // Determine planned action
-        entry.planned_action = Some(self.determine_planned_action(&entry));
+        let (action, decided_by) = self.determine_planned_action(&entry);
+        entry.planned_action = Some(action);
+        entry.decided_by = Some(decided_by);
 
         Ok(entry)
     }
 
-    /// Determine the cache kind for a path
-    fn determine_cache_kind(&self, path: &Path) -> CacheKind {
-        let path_str = path.to_string_lossy().to_lowercase();
-
-        // JavaScript/TypeScript caches
-        if path_str.contains("node_modules")
-            || path_str.contains(".next")
-            || path_str.contains(".nuxt")
-            || path_str.contains(".vite")
-            || path_str.contains(".turbo")
-            || path_str.contains(".parcel-cache")
-        {
-            return CacheKind::JavaScript;
-        }
-
-        // Python caches
-        if path_str.contains("__pycache__")
-            || path_str.contains(".pytest_cache")
-            || path_str.contains(".venv")
-            || path_str.contains("venv")
-            || path_str.contains(".tox")
-            || path_str.contains(".mypy_cache")
-            || path_str.contains(".ruff_cache")
-            || path_str.contains(".pip-cache")
-        {
-            return CacheKind::Python;
-        }
-
-        // Rust caches
-        if path_str.contains("target") || path_str.contains("cargo") {
-            return CacheKind::Rust;
-        }
-
-        // Java caches
-        if path_str.contains(".gradle")
-            || path_str.contains("build") && path_str.contains("gradle")
-            || path_str.contains(".m2")
-        {
-            return CacheKind::Java;
-        }
-
-        // ML/AI caches
-        if path_str.contains("huggingface")
-            || path_str.contains("torch")
-            || path_str.contains("transformers")
-            || path_str.contains(".dvc")
-            || path_str.contains("wandb")
-        {
-            return CacheKind::MachineLearning;
-        }
-
-        // NPX caches
-        if path_str.contains("_npx") {
-            return CacheKind::Npx;
-        }
-
-        // Docker caches
-        if path_str.contains("docker") {
-            return CacheKind::Docker;
-        }
-
-        CacheKind::Generic
-    }
-
     /// Calculate the size of a cache path
     fn calculate_size(&self, path: &Path) -> Result<u64> {
         if !path_exists(path) {
@@ -163,23 +146,33 @@ impl CacheInspector {
         days_since_used > self.config.stale_days as i64
     }
 
-    /// Determine the planned action for a cache entry
-    fn determine_planned_action(&self, entry: &CacheEntry) -> PlannedAction {
+    /// Determine the planned action for a cache entry, and what decided it.
+    /// Safety checks and excludes come first, then `[[rules]]`, then the default.
+    fn determine_planned_action(&self, entry: &CacheEntry) -> (PlannedAction, String) {
+        // Skip caches refused by verification or because git tracks files in them
+        if let Some(reason) = &entry.skip_reason {
+            return (PlannedAction::Skip, format!("safety: {}", reason));
+        }
+
         // Skip if path should be excluded
         if self.config.should_exclude_path(&entry.path) {
-            return PlannedAction::Skip;
+            return (PlannedAction::Skip, "exclude pattern".to_string());
         }
 
         // Skip if not included in paths
         if !self.config.paths.is_empty() && !self.config.should_include_path(&entry.path) {
-            return PlannedAction::Skip;
+            return (PlannedAction::Skip, "not in --paths".to_string());
+        }
+
+        if let Some(decision) = RuleSet::new(&self.config.rules).decide(entry) {
+            return decision;
         }
 
         // Use safe delete if enabled
         if self.config.safe_delete {
-            PlannedAction::Backup
+            (PlannedAction::Backup, "default: safe delete".to_string())
         } else {
-            PlannedAction::Delete
+            (PlannedAction::Delete, "default: delete".to_string())
         }
     }
 
@@ -220,6 +213,56 @@ impl CacheInspector {
         }
     }
 
+    /// Evict least recently used entries until all caches fit in `budget` bytes.
+    /// Entries already skipped (excluded, protected or pinned by a `skip` rule) are
+    /// never evicted but still count; entries that fit are marked to skip.
+    pub fn apply_budget(&self, entries: &mut [CacheEntry], budget: u64) -> BudgetResult {
+        let total_size = self.get_summary(entries).total_size;
+        let pinned_size: u64 = entries
+            .iter()
+            .filter(|e| !is_evictable(e))
+            .map(|e| e.size_bytes)
+            .sum();
+
+        let mut order: Vec<usize> = (0..entries.len())
+            .filter(|&i| is_evictable(&entries[i]))
+            .collect();
+        order.sort_by_key(|&i| entries[i].last_used);
+
+        let mut remaining = total_size;
+        let mut evicted_count = 0;
+        let mut evicted_size = 0;
+        for i in order {
+            let entry = &mut entries[i];
+            if remaining > budget {
+                let freed = entry.freed_bytes();
+                remaining -= freed;
+                evicted_size += freed;
+                evicted_count += 1;
+                entry.decided_by = Some(format!(
+                    "max_total_cache: least recently used, then {}",
+                    entry.decided_by.as_deref().unwrap_or("default")
+                ));
+            } else {
+                entry.planned_action = Some(PlannedAction::Skip);
+                entry.skip_reason = Some(format!(
+                    "fits in max_total_cache ({})",
+                    humansize::format_size(budget, humansize::DECIMAL)
+                ));
+                entry.decided_by = Some("max_total_cache: fits".to_string());
+            }
+        }
+
+        BudgetResult {
+            budget,
+            total_size,
+            pinned_size,
+            evicted_count,
+            evicted_size,
+            final_size: remaining,
+        }
+    }
+
     /// Get the top N largest cache entries
     pub fn get_largest_entries<'a>(
         &self,
@@ -227,11 +270,37 @@ impl CacheInspector {
         n: usize,
     ) -> Vec<&'a CacheEntry> {
         let mut sorted_entries: Vec<_> = entries.iter().collect();
-        sorted_entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        sorted_entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
         sorted_entries.into_iter().take(n).collect()
     }
 }
 
+/// Whether a cache budget may evict this entry
+fn is_evictable(entry: &CacheEntry) -> bool {
+    !matches!(entry.planned_action, Some(PlannedAction::Skip) | None)
+}
+
+/// What `max_total_cache` evicts
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BudgetResult {
+    pub budget: u64,
+    /// All caches before eviction
+    pub total_size: u64,
+    /// Caches that are never evicted
+    pub pinned_size: u64,
+    pub evicted_count: usize,
+    pub evicted_size: u64,
+    /// All caches after eviction
+    pub final_size: u64,
+}
+
+impl BudgetResult {
+    /// Whether the caches fit in the budget after eviction
+    pub fn met(&self) -> bool {
+        self.final_size <= self.budget
+    }
+}
+
 /// Summary statistics for cache entries
 #[derive(Debug, Clone)]
 pub struct CacheSummary {
@@ -271,7 +340,6 @@ mod tests {
 
     fn create_test_config() -> MergedConfig {
         MergedConfig {
-            list: false,
             dry_run: false,
             force: false,
             json: false,
@@ -283,31 +351,21 @@ mod tests {
             backup_dir: ".cachekill-backup".to_string(),
             docker: false,
             npx: false,
-            restore_last: false,
             all: false,
             js_pm: false,
+            npx_packages: vec![],
+            keep_versions: None,
+            gitignored: false,
+            min_size: crate::config::DEFAULT_MIN_SIZE,
+            rules: vec![],
+            explain: false,
+            free_target: None,
+            max_total_cache: None,
+            rehydrate: None,
+            kill_holders: false,
         }
     }
 
-    #[test]
-    fn test_cache_kind_detection() {
-        let config = create_test_config();
-        let inspector = CacheInspector::new(config);
-
-        assert_eq!(
-            inspector.determine_cache_kind(Path::new("node_modules")),
-            CacheKind::JavaScript
-        );
-        assert_eq!(
-            inspector.determine_cache_kind(Path::new("__pycache__")),
-            CacheKind::Python
-        );
-        assert_eq!(
-            inspector.determine_cache_kind(Path::new("target")),
-            CacheKind::Rust
-        );
-    }
-
     #[test]
     fn test_size_calculation() {
         let temp_dir = TempDir::new().unwrap();
@@ -397,4 +455,38 @@ mod tests {
         assert_eq!(largest[0].path.to_string_lossy(), "large");
         assert_eq!(largest[1].path.to_string_lossy(), "medium");
     }
+
+    #[test]
+    fn test_apply_budget_evicts_least_recently_used() {
+        let config = create_test_config();
+        let inspector = CacheInspector::new(config);
+        let entry = |name: &str, size: u64, days_ago: i64, action: PlannedAction| {
+            CacheEntry::new(
+                std::path::PathBuf::from(name),
+                CacheKind::JavaScript,
+                size,
+                Utc::now() - chrono::Duration::days(days_ago),
+                false,
+            )
+            .with_planned_action(action)
+        };
+        let mut entries = vec![
+            entry("recent", 400, 1, PlannedAction::Delete),
+            entry("oldest", 300, 90, PlannedAction::Delete),
+            entry("pinned", 500, 120, PlannedAction::Skip),
+            entry("older", 200, 30, PlannedAction::Backup),
+        ];
+
+        let result = inspector.apply_budget(&mut entries, 1000);
+        assert_eq!(result.total_size, 1400);
+        assert_eq!(result.pinned_size, 500);
+        assert_eq!(result.evicted_count, 2);
+        assert_eq!(result.final_size, 900);
+        assert!(result.met());
+
+        assert_eq!(entries[1].planned_action, Some(PlannedAction::Delete));
+        assert_eq!(entries[3].planned_action, Some(PlannedAction::Backup));
+        assert_eq!(entries[0].planned_action, Some(PlannedAction::Skip));
+        assert_eq!(entries[2].skip_reason, None);
+    }
 }
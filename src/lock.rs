@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+#[cfg(not(unix))]
+use sysinfo::{Pid, ProcessRefreshKind, System};
+
+/// Lock file name inside the state directory
+pub const LOCK_FILE: &str = "run.lock";
+
+/// How often a waiting run checks the lock again
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Exclusive right to change caches and backups, released when dropped or on exit.
+/// On unix the kernel drops the flock when its holder dies, so a lock is never stale.
+#[derive(Debug)]
+pub struct RunLock {
+    // The lock lives as long as the open file
+    _file: File,
+}
+
+/// The run recorded in a lock file
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub started: Option<DateTime<Utc>>,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            started: Some(Utc::now()),
+        }
+    }
+
+    fn parse(contents: &str) -> Option<Self> {
+        let mut fields = contents.split_whitespace();
+        let pid = fields.next()?.parse().ok()?;
+        let started = fields
+            .next()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc));
+        Some(Self { pid, started })
+    }
+
+    #[cfg(not(unix))]
+    fn alive(&self) -> bool {
+        System::new().refresh_process_specifics(Pid::from_u32(self.pid), ProcessRefreshKind::new())
+    }
+
+    fn describe(&self) -> String {
+        match self.started {
+            Some(started) => format!(
+                "pid {}, started {}",
+                self.pid,
+                started.format("%Y-%m-%d %H:%M:%S UTC")
+            ),
+            None => format!("pid {}", self.pid),
+        }
+    }
+}
+
+/// Directory holding the lock every run shares, wherever it starts:
+/// `$XDG_STATE_HOME/cachekill`, else the platform state or cache directory
+pub fn state_dir() -> PathBuf {
+    std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(dirs::state_dir)
+        .or_else(dirs::cache_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("cachekill")
+}
+
+impl RunLock {
+    /// Lock `dir` for this run. When another run holds it, wait for it or fail.
+    pub fn acquire(dir: &Path, wait: bool) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(LOCK_FILE);
+        let mut announced = false;
+
+        loop {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            let owner = read_owner(&mut file);
+
+            if try_lock(&file, owner.as_ref())? {
+                write_owner(&mut file, &LockOwner::current())?;
+                return Ok(Self { _file: file });
+            }
+
+            // The recorded pid only names the holder; whether the lock is held is up to flock
+            let holder = owner
+                .map(|o| o.describe())
+                .unwrap_or_else(|| "unknown pid".to_string());
+            if !wait {
+                anyhow::bail!(
+                    "another cachekill run ({}) holds {}; rerun with --wait to wait for it",
+                    holder,
+                    path.display()
+                );
+            }
+            if !announced {
+                eprintln!(
+                    "Waiting for another cachekill run ({}) to finish...",
+                    holder
+                );
+                announced = true;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+fn read_owner(file: &mut File) -> Option<LockOwner> {
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).ok()?;
+    LockOwner::parse(&contents)
+}
+
+fn write_owner(file: &mut File, owner: &LockOwner) -> Result<()> {
+    let started = owner.started.map(|t| t.to_rfc3339()).unwrap_or_default();
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    writeln!(file, "{} {}", owner.pid, started).context("Failed to write lock file")?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn try_lock(file: &File, _owner: Option<&LockOwner>) -> Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: the descriptor belongs to `file`, which outlives the call
+    let rc = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+    if rc == 0 {
+        return Ok(true);
+    }
+    let err = std::io::Error::last_os_error();
+    if err.kind() == std::io::ErrorKind::WouldBlock {
+        Ok(false)
+    } else {
+        Err(err).context("Failed to lock")
+    }
+}
+
+/// Without flock the recorded owner decides: a live run other than this one holds the lock,
+/// and a dead one left a stale lock behind
+#[cfg(not(unix))]
+fn try_lock(_file: &File, owner: Option<&LockOwner>) -> Result<bool> {
+    Ok(owner.map_or(true, |o| o.pid == std::process::id() || !o.alive()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_second_run_waits_or_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock = RunLock::acquire(temp_dir.path(), false).unwrap();
+        let owner = std::fs::read_to_string(temp_dir.path().join(LOCK_FILE)).unwrap();
+        assert_eq!(
+            LockOwner::parse(&owner).map(|o| o.pid),
+            Some(std::process::id())
+        );
+
+        let err = RunLock::acquire(temp_dir.path(), false).unwrap_err();
+        assert!(err.to_string().contains("--wait"));
+
+        let dir = temp_dir.path().to_path_buf();
+        let waiter = std::thread::spawn(move || RunLock::acquire(&dir, true).map(|_| ()));
+        std::thread::sleep(POLL_INTERVAL * 2);
+        assert!(!waiter.is_finished());
+        drop(lock);
+        waiter.join().unwrap().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_held_lock_with_dead_pid_is_not_taken() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOCK_FILE);
+        // No process has this pid, as pid_max is at most 2^22
+        std::fs::write(&path, "4294967 2024-01-01T00:00:00+00:00\n").unwrap();
+        let held = File::open(&path).unwrap();
+        assert!(try_lock(&held, None).unwrap());
+
+        // A live descriptor still holds the flock, whatever pid the file records
+        let err = RunLock::acquire(temp_dir.path(), false).unwrap_err();
+        assert!(err.to_string().contains("--wait"));
+        assert!(err.to_string().contains("pid 4294967"));
+        assert!(path.exists());
+
+        drop(held);
+        RunLock::acquire(temp_dir.path(), false).unwrap();
+    }
+}
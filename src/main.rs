@@ -1,35 +1,54 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::path::PathBuf;
 use std::process;
 
 mod actions;
+mod activity;
 mod cache_entry;
+mod cachedir_tag;
 mod ci;
 mod config;
+mod detectors;
 mod discover;
 mod docker;
 mod doctor;
 mod edge;
+mod freespace;
+mod git;
 mod hf;
+mod holders;
+mod init;
 mod inspect;
+mod lock;
 mod npx;
 mod output;
 mod package_managers;
+mod picker;
+mod regen;
+mod rehydrate;
+mod rules;
+mod scan;
 mod torch;
 mod util;
 
-use crate::cache_entry::{CacheEntry, PlannedAction};
+use crate::cache_entry::{CacheEntry, LanguageFilter, PlannedAction};
 use actions::ActionExecutor;
 use ci::{handle_ci_mode, CiMode};
 use config::{CliArgs, Config, MergedConfig};
+use detectors::DetectorRegistry;
 use discover::DiscoveryResult;
 use docker::DockerCacheManager;
 use doctor::handle_doctor;
 use edge::{
     handle_cloudflare_purge, handle_cloudflare_status, handle_vercel_purge, handle_vercel_status,
 };
+use freespace::{DiskSpace, FreePlan, FreeTarget};
 use hf::{handle_hf_clean, handle_hf_list};
 use inspect::CacheInspector;
+use lock::RunLock;
 use npx::NpxCacheManager;
 use output::OutputFormatter;
 use torch::{handle_torch_clean, handle_torch_list};
@@ -46,43 +65,177 @@ provides safe deletion with backup functionality, and offers detailed insights
 into your cache usage.
 
 Examples:
-  cachekill                    # Clean detected caches with confirmation
-  cachekill --dry-run          # Show what would be cleaned
-  cachekill --list             # List all cache entries with details
-  cachekill --lang js --force  # Clean JavaScript caches without confirmation
-  cachekill --docker           # Include Docker cleanup
-  cachekill --npx --list       # List NPX cache contents
-  cachekill --ci prebuild      # CI mode for prebuild
-  cachekill --hf --list        # List HuggingFace cache
-  cachekill --torch            # Clean PyTorch cache
-  cachekill --vercel --list    # Check Vercel integration status
-  cachekill --cloudflare       # Purge Cloudflare edge cache
-  cachekill --doctor           # System diagnostics
+  cachekill clean              # Clean detected caches with confirmation
+  cachekill plan               # Show what would be cleaned
+  cachekill list               # List all cache entries with details
+  cachekill clean --lang js --force  # Clean JavaScript caches without confirmation
+  cachekill clean --docker     # Include Docker cleanup
+  cachekill list --npx         # List NPX cache contents
+  cachekill clean --npx --package create-react-app  # Remove one NPX package
+  cachekill clean --npx --keep-versions 2           # Keep 2 newest versions per package
+  cachekill ci prebuild        # CI mode for prebuild
+  cachekill hf list            # List HuggingFace cache
+  cachekill torch clean        # Clean PyTorch cache
+  cachekill edge status vercel # Check Vercel integration status
+  cachekill edge purge cloudflare --zone ZONE_ID  # Purge Cloudflare edge cache
+  cachekill doctor             # System diagnostics
+  cachekill backups            # Backups made by safe delete
+  cachekill restore            # Restore the most recent backup
+  cachekill plan --gitignored  # Every ignored directory over 10MB, largest first
+  cachekill tag                # Mark detected caches with CACHEDIR.TAG
+  cachekill verify             # Check JS package manager caches
+  cachekill plan --explain     # Show which rule decided each action
+  cachekill scan ~/code ~/work --inactive-months 3  # Caches of every project, idle ones marked
+  cachekill config show --origin   # Effective settings and the layer that set each
+  cachekill config check           # Validate config files and CACHEKILL_* variables
+  cachekill --profile deep plan    # Apply the [profiles.deep] preset
+  cachekill init               # Generate a .cachekillrc for this project
+
+Running without a command cleans, like `cachekill clean`. The older flags
+(--list, --doctor, --ci, --hf, --torch, --vercel, --cloudflare, --restore-last,
+--verify, --tag-caches)
+still work but are deprecated.
 "#)]
 struct Cli {
-    /// List cache entries with size, last-used, and stale information
-    #[arg(long)]
-    list: bool,
-
     /// Show what would be removed without actually doing it
-    #[arg(long)]
+    #[arg(long, global = true)]
     dry_run: bool,
 
     /// Proceed without interactive prompt
-    #[arg(short = 'f', long)]
+    #[arg(short = 'f', long, global = true)]
     force: bool,
 
     /// Alias for --force
-    #[arg(short = 'y', long)]
+    #[arg(short = 'y', long, global = true)]
     yes: bool,
 
     /// Output in JSON format
-    #[arg(long)]
+    #[arg(long, global = true)]
     json: bool,
 
+    /// Show which safety check, rule or default decided each entry's action
+    #[arg(long, global = true)]
+    explain: bool,
+
+    /// Apply a named preset from `[profiles.<NAME>]` in the configuration
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Wait for another cachekill run to finish (the default)
+    #[arg(long, global = true, overrides_with = "no_wait")]
+    wait: bool,
+
+    /// Fail at once when another cachekill run holds the lock
+    #[arg(long, global = true, overrides_with = "wait")]
+    no_wait: bool,
+
+    /// Selection used when no command is given
+    #[command(flatten)]
+    select: SelectArgs,
+
+    #[command(flatten)]
+    legacy: LegacyArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Flags from before subcommands existed, mapped onto the equivalent command.
+/// One mode at most, and none with options its command would ignore.
+#[derive(Args, Clone, Default)]
+#[command(next_help_heading = "Deprecated flags")]
+#[command(group(ArgGroup::new("legacy_mode").args([
+    "restore_last", "doctor", "ci", "hf", "torch", "vercel", "cloudflare", "verify", "tag_caches",
+])))]
+#[command(group(ArgGroup::new("edge_provider").args(["vercel", "cloudflare"])))]
+struct LegacyArgs {
+    /// Use `cachekill list`
+    #[arg(long, conflicts_with = "dry_run")]
+    list: bool,
+
+    /// Use `cachekill restore`
+    #[arg(long, conflicts_with_all = ["dry_run", "docker", "npx"])]
+    restore_last: bool,
+
+    /// Use `cachekill doctor`
+    #[arg(long, conflicts_with_all = ["dry_run", "docker", "npx"])]
+    doctor: bool,
+
+    /// Use `cachekill ci <MODE>`
+    #[arg(long, value_name = "MODE", conflicts_with_all = ["docker", "npx"])]
+    ci: Option<String>,
+
+    /// Use `cachekill hf list|clean`
+    #[arg(long, conflicts_with_all = ["dry_run", "docker", "npx"])]
+    hf: bool,
+
+    /// Use `cachekill torch list|clean`
+    #[arg(long, conflicts_with_all = ["dry_run", "docker", "npx"])]
+    torch: bool,
+
+    /// Use `cachekill edge status|purge vercel`
+    #[arg(long, conflicts_with_all = ["docker", "npx"])]
+    vercel: bool,
+
+    /// Use `cachekill edge status|purge cloudflare`
+    #[arg(long, conflicts_with_all = ["docker", "npx"])]
+    cloudflare: bool,
+
+    /// Use `cachekill verify`
+    #[arg(long, conflicts_with_all = ["dry_run", "docker", "npx", "list"])]
+    verify: bool,
+
+    /// Use `cachekill tag`
+    #[arg(long, conflicts_with_all = ["docker", "npx", "list"])]
+    tag_caches: bool,
+
+    /// Use `cachekill hf clean --model`
+    #[arg(long, value_name = "MODEL_ID", requires = "hf")]
+    model: Option<String>,
+
+    /// Use `cachekill edge purge vercel --project`
+    #[arg(long, value_name = "PROJECT_ID", requires = "vercel")]
+    project: Option<String>,
+
+    /// Use `cachekill edge purge cloudflare --zone`
+    #[arg(long, value_name = "ZONE_ID", requires = "cloudflare")]
+    zone: Option<String>,
+
+    /// Use `cachekill edge purge <PROVIDER> --token`
+    #[arg(long, value_name = "TOKEN", requires = "edge_provider")]
+    token: Option<String>,
+}
+
+impl LegacyArgs {
+    /// The first deprecated flag given, if any
+    fn first_flag(&self) -> Option<&'static str> {
+        [
+            (self.list, "--list"),
+            (self.restore_last, "--restore-last"),
+            (self.doctor, "--doctor"),
+            (self.ci.is_some(), "--ci"),
+            (self.hf, "--hf"),
+            (self.torch, "--torch"),
+            (self.vercel, "--vercel"),
+            (self.cloudflare, "--cloudflare"),
+            (self.verify, "--verify"),
+            (self.tag_caches, "--tag-caches"),
+            (self.model.is_some(), "--model"),
+            (self.project.is_some(), "--project"),
+            (self.zone.is_some(), "--zone"),
+            (self.token.is_some(), "--token"),
+        ]
+        .into_iter()
+        .find_map(|(set, flag)| set.then_some(flag))
+    }
+}
+
+/// Which caches a command looks at
+#[derive(Args, Clone, Default)]
+struct SelectArgs {
     /// Language filter (auto, js, py, rust, java, ml)
     #[arg(long, value_name = "LANG")]
-    lang: Option<String>,
+    lang: Option<LanguageFilter>,
 
     /// Additional include paths (glob patterns)
     #[arg(long, value_name = "PATTERNS")]
@@ -112,90 +265,397 @@ struct Cli {
     #[arg(long)]
     npx: bool,
 
-    /// Restore from last backup
-    #[arg(long)]
-    restore_last: bool,
-
     /// Clean all common caches regardless of project type
     #[arg(long)]
     all: bool,
 
-    /// System diagnostics
+    /// JavaScript package managers npm, pnpm, yarn, bun, deno
     #[arg(long)]
-    doctor: bool,
+    js_pm: bool,
 
-    /// CI mode for non-interactive cache management
-    #[arg(long, value_name = "MODE")]
-    ci: Option<String>,
+    /// NPX packages to remove by name (comma-separated)
+    #[arg(long, value_name = "NAMES", requires = "npx")]
+    package: Option<String>,
 
-    /// HuggingFace cache operations
-    #[arg(long)]
-    hf: bool,
+    /// Keep the N most recently used versions of each NPX package
+    #[arg(long, value_name = "N", requires = "npx")]
+    keep_versions: Option<usize>,
 
-    /// PyTorch cache operations
+    /// Propose every gitignored directory in the repository, largest first
     #[arg(long)]
-    torch: bool,
+    gitignored: bool,
 
-    /// Vercel edge cache operations
-    #[arg(long)]
-    vercel: bool,
+    /// Smallest directory proposed by --gitignored (e.g. 50MB, 1GiB; default 10MB)
+    #[arg(long, value_name = "SIZE", requires = "gitignored", value_parser = parse_size_arg)]
+    min_size: Option<u64>,
+
+    /// Clean the best candidates only until this much is reclaimed (e.g. 30GB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size_arg, conflicts_with = "until_free")]
+    free: Option<u64>,
 
-    /// Cloudflare edge cache operations
+    /// Clean the best candidates only until the filesystem has this much free (e.g. 50GB, 15%)
+    #[arg(long, value_name = "SIZE|PERCENT", value_parser = parse_free_target_arg)]
+    until_free: Option<FreeTarget>,
+
+    /// Stop processes using a cache (e.g. a running build) instead of skipping the cache
     #[arg(long)]
-    cloudflare: bool,
+    kill_holders: bool,
+}
 
-    /// Target specific model ID for HuggingFace
-    #[arg(long, value_name = "MODEL_ID")]
-    model: Option<String>,
+impl SelectArgs {
+    /// These options, falling back to `outer` (given before the command) where unset
+    fn or(&self, outer: &SelectArgs) -> SelectArgs {
+        SelectArgs {
+            lang: self.lang.or(outer.lang),
+            paths: self.paths.clone().or_else(|| outer.paths.clone()),
+            exclude: self.exclude.clone().or_else(|| outer.exclude.clone()),
+            stale_days: self.stale_days.or(outer.stale_days),
+            safe_delete: self.safe_delete.or(outer.safe_delete),
+            backup_dir: self.backup_dir.clone().or_else(|| outer.backup_dir.clone()),
+            docker: self.docker || outer.docker,
+            npx: self.npx || outer.npx,
+            all: self.all || outer.all,
+            js_pm: self.js_pm || outer.js_pm,
+            package: self.package.clone().or_else(|| outer.package.clone()),
+            keep_versions: self.keep_versions.or(outer.keep_versions),
+            gitignored: self.gitignored || outer.gitignored,
+            min_size: self.min_size.or(outer.min_size),
+            // One target at most, so an outer --free cannot join an inner --until-free
+            free: if self.has_free_target() {
+                self.free
+            } else {
+                outer.free
+            },
+            until_free: if self.has_free_target() {
+                self.until_free
+            } else {
+                outer.until_free
+            },
+            kill_holders: self.kill_holders || outer.kill_holders,
+        }
+    }
 
-    /// Target specific project ID for Vercel
-    #[arg(long, value_name = "PROJECT_ID")]
-    project: Option<String>,
+    fn has_free_target(&self) -> bool {
+        self.free.is_some() || self.until_free.is_some()
+    }
 
-    /// Target specific zone ID for Cloudflare
-    #[arg(long, value_name = "ZONE_ID")]
-    zone: Option<String>,
+    fn free_target(&self) -> Option<FreeTarget> {
+        self.free.map(FreeTarget::Reclaim).or(self.until_free)
+    }
+}
 
-    /// API token for edge cache purging
-    #[arg(long, value_name = "TOKEN")]
-    token: Option<String>,
+/// Options of `clean`
+#[derive(Args, Clone, Default)]
+struct CleanArgs {
+    #[command(flatten)]
+    select: SelectArgs,
 
-    /// JavaScript package managers npm, pnpm, yarn
+    /// Afterwards, run each cleaned project's regenerate command (npm ci, uv sync, ...)
     #[arg(long)]
-    js_pm: bool,
+    rehydrate: bool,
+
+    /// Regenerate commands run at once by --rehydrate
+    #[arg(long, value_name = "N", requires = "rehydrate", default_value_t = rehydrate::DEFAULT_JOBS)]
+    rehydrate_jobs: usize,
+}
+
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// List cache entries with size, last-used, and stale information
+    List(SelectArgs),
+    /// Clean detected caches after one confirmation
+    Clean(CleanArgs),
+    /// Show what `clean` would do without changing anything
+    Plan(SelectArgs),
+    /// Restore the most recent backup, or the one named
+    Restore {
+        /// Backup name as shown by `cachekill backups`
+        #[arg(value_name = "NAME")]
+        backup: Option<String>,
+    },
+    /// List backups made by safe delete
+    Backups {
+        /// Delete backups older than this many days first
+        #[arg(long, value_name = "DAYS")]
+        remove_older_than: Option<u32>,
+    },
+    /// System diagnostics
+    Doctor,
+    /// Check JS package manager caches (the tool's own check when installed)
+    Verify,
+    /// Write CACHEDIR.TAG into detected caches so backup tools (restic, borg) skip them
+    Tag(SelectArgs),
+    /// Non-interactive cache management for CI pipelines
+    Ci {
+        #[arg(value_enum)]
+        mode: CiMode,
+    },
+    /// HuggingFace model cache
+    #[command(subcommand)]
+    Hf(HfCommand),
+    /// PyTorch hub cache
+    #[command(subcommand)]
+    Torch(TorchCommand),
+    /// CDN edge caches (Vercel, Cloudflare)
+    #[command(subcommand)]
+    Edge(EdgeCommand),
+    /// Find every project under one or more roots and report its caches
+    Scan(ScanArgs),
+    /// Inspect the layered configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Write a .cachekillrc tailored to this project (--force overwrites, --dry-run prints it)
+    Init,
+}
+
+#[derive(Subcommand, Clone)]
+enum HfCommand {
+    /// List cached models
+    List,
+    /// Remove cached models
+    Clean {
+        /// Only remove this model (e.g. bert-base-uncased)
+        #[arg(long, value_name = "MODEL_ID")]
+        model: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum TorchCommand {
+    /// List cached hub entries
+    List,
+    /// Remove the hub cache
+    Clean,
+}
+
+#[derive(Subcommand, Clone)]
+enum EdgeCommand {
+    /// Show whether a provider's CLI and token are available
+    Status {
+        #[arg(value_enum)]
+        provider: EdgeProvider,
+    },
+    /// Purge a provider's edge cache
+    #[command(subcommand)]
+    Purge(EdgePurge),
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum EdgeProvider {
+    Vercel,
+    Cloudflare,
+}
+
+#[derive(Subcommand, Clone)]
+enum EdgePurge {
+    /// Purge Vercel's cache via the vercel CLI, or the API with a token
+    Vercel {
+        /// Project to purge (required by the API)
+        #[arg(long, value_name = "PROJECT_ID")]
+        project: Option<String>,
+        /// API token; defaults to VERCEL_TOKEN
+        #[arg(long, value_name = "TOKEN")]
+        token: Option<String>,
+    },
+    /// Purge Cloudflare's cache via wrangler, or the API with a token
+    Cloudflare {
+        /// Zone to purge
+        #[arg(long, value_name = "ZONE_ID")]
+        zone: Option<String>,
+        /// API token; defaults to CF_API_TOKEN
+        #[arg(long, value_name = "TOKEN")]
+        token: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum ConfigCommand {
+    /// Print every effective setting after all layers and CLI flags
+    Show {
+        /// Also print which layer (system, user, project, env, cli) set each value
+        #[arg(long)]
+        origin: bool,
+    },
+    /// Validate every configuration layer; exits with code 4 when invalid
+    Check,
+}
+
+#[derive(Args, Clone)]
+struct ScanArgs {
+    /// Directories to search for projects (default: the current directory)
+    #[arg(value_name = "ROOT")]
+    roots: Vec<PathBuf>,
+
+    /// How many levels below each root to look for projects
+    #[arg(long, default_value_t = scan::DEFAULT_DEPTH)]
+    depth: usize,
+
+    /// Select projects untouched for at least N months (30 days each)
+    #[arg(long, value_name = "N")]
+    inactive_months: Option<u32>,
+
+    /// Clean every cache in the selected projects after one confirmation
+    #[arg(long, requires = "inactive_months")]
+    clean: bool,
+}
+
+fn parse_size_arg(value: &str) -> std::result::Result<u64, String> {
+    util::parse_size(value).map_err(|e| e.to_string())
+}
+
+fn parse_free_target_arg(value: &str) -> std::result::Result<FreeTarget, String> {
+    FreeTarget::parse_until(value).map_err(|e| e.to_string())
 }
 
 impl Cli {
+    /// Legacy flags select a command themselves, so they cannot be combined with one
+    fn check_legacy(&self) -> std::result::Result<(), clap::Error> {
+        match (&self.command, self.legacy.first_flag()) {
+            (Some(_), Some(flag)) => Err(<Cli as clap::CommandFactory>::command().error(
+                clap::error::ErrorKind::ArgumentConflict,
+                format!("{} cannot be used with a subcommand", flag),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The command to run: the subcommand, or the one the deprecated flags stand for
+    fn resolve_command(&self) -> Command {
+        if let Some(command) = &self.command {
+            return command.clone();
+        }
+
+        let legacy = &self.legacy;
+        let deprecated = |flag: &str, replacement: &str| {
+            eprintln!(
+                "warning: {} is deprecated; use `cachekill {}`",
+                flag, replacement
+            );
+        };
+        if legacy.verify {
+            deprecated("--verify", "verify");
+            return Command::Verify;
+        }
+        if legacy.tag_caches {
+            deprecated("--tag-caches", "tag");
+            return Command::Tag(SelectArgs::default());
+        }
+        if legacy.restore_last {
+            deprecated("--restore-last", "restore");
+            return Command::Restore { backup: None };
+        }
+        if legacy.doctor {
+            deprecated("--doctor", "doctor");
+            return Command::Doctor;
+        }
+        if let Some(mode) = &legacy.ci {
+            deprecated("--ci", &format!("ci {}", mode));
+            let mode = match mode.as_str() {
+                "prebuild" => CiMode::Prebuild,
+                "postbuild" => CiMode::Postbuild,
+                _ => {
+                    eprintln!("Invalid CI mode: {}. Use 'prebuild' or 'postbuild'", mode);
+                    process::exit(ci::exit_codes::CONFIG_ERROR);
+                }
+            };
+            return Command::Ci { mode };
+        }
+        if legacy.hf {
+            if legacy.list {
+                deprecated("--hf --list", "hf list");
+                return Command::Hf(HfCommand::List);
+            }
+            deprecated("--hf", "hf clean");
+            return Command::Hf(HfCommand::Clean {
+                model: legacy.model.clone(),
+            });
+        }
+        if legacy.torch {
+            if legacy.list {
+                deprecated("--torch --list", "torch list");
+                return Command::Torch(TorchCommand::List);
+            }
+            deprecated("--torch", "torch clean");
+            return Command::Torch(TorchCommand::Clean);
+        }
+        if legacy.vercel || legacy.cloudflare {
+            let (provider, name) = if legacy.vercel {
+                (EdgeProvider::Vercel, "vercel")
+            } else {
+                (EdgeProvider::Cloudflare, "cloudflare")
+            };
+            if legacy.list {
+                deprecated(
+                    &format!("--{} --list", name),
+                    &format!("edge status {}", name),
+                );
+                return Command::Edge(EdgeCommand::Status { provider });
+            }
+            deprecated(&format!("--{}", name), &format!("edge purge {}", name));
+            let token = legacy.token.clone();
+            return Command::Edge(EdgeCommand::Purge(match provider {
+                EdgeProvider::Vercel => EdgePurge::Vercel {
+                    project: legacy.project.clone(),
+                    token,
+                },
+                EdgeProvider::Cloudflare => EdgePurge::Cloudflare {
+                    zone: legacy.zone.clone(),
+                    token,
+                },
+            }));
+        }
+        if legacy.list {
+            deprecated("--list", "list");
+            return Command::List(SelectArgs::default());
+        }
+        Command::Clean(CleanArgs::default())
+    }
+
     /// Convert CLI arguments to CliArgs struct
-    fn to_cli_args(&self) -> CliArgs {
+    fn to_cli_args(&self, command: &Command) -> CliArgs {
+        let select = match command {
+            Command::List(select)
+            | Command::Clean(CleanArgs { select, .. })
+            | Command::Plan(select)
+            | Command::Tag(select) => select.or(&self.select),
+            _ => self.select.clone(),
+        };
+        let split = |s: &String| s.split(',').map(|s| s.trim().to_string()).collect();
+
         CliArgs {
-            list: self.list,
-            dry_run: self.dry_run,
+            dry_run: self.dry_run || matches!(command, Command::Plan(_)),
             force: self.force || self.yes,
             json: self.json,
-            lang: self.lang.as_ref().and_then(|s| s.parse().ok()),
-            paths: self
-                .paths
-                .as_ref()
-                .map(|s| s.split(',').map(|s| s.trim().to_string()).collect()),
-            exclude: self
-                .exclude
-                .as_ref()
-                .map(|s| s.split(',').map(|s| s.trim().to_string()).collect()),
-            stale_days: self.stale_days,
-            safe_delete: self.safe_delete,
-            backup_dir: self.backup_dir.clone(),
-            docker: self.docker,
-            npx: self.npx,
-            restore_last: self.restore_last,
-            all: self.all,
-            js_pm: self.js_pm,
+            lang: select.lang,
+            paths: select.paths.as_ref().map(split),
+            exclude: select.exclude.as_ref().map(split),
+            stale_days: select.stale_days,
+            safe_delete: select.safe_delete,
+            backup_dir: select.backup_dir.clone(),
+            docker: select.docker,
+            npx: select.npx,
+            all: select.all,
+            js_pm: select.js_pm,
+            npx_packages: select.package.as_ref().map(split),
+            keep_versions: select.keep_versions,
+            gitignored: select.gitignored,
+            min_size: select.min_size,
+            explain: self.explain,
+            free_target: select.free_target(),
+            rehydrate: match command {
+                Command::Clean(args) if args.rehydrate => Some(args.rehydrate_jobs),
+                _ => None,
+            },
+            kill_holders: select.kill_holders,
         }
     }
 }
 
 fn main() {
-    let cli = Parser::parse();
+    let cli: Cli = Parser::parse();
+    if let Err(e) = cli.check_legacy() {
+        e.exit();
+    }
 
     // Run the main application
     if let Err(e) = run(cli) {
@@ -205,98 +665,115 @@ fn main() {
 }
 
 fn run(cli: Cli) -> Result<()> {
+    let command = cli.resolve_command();
+
+    // `init --force` must be able to replace a broken config, so it never loads one
+    if let Command::Init = command {
+        let interactive = std::io::stdin().is_terminal() && !cli.yes && !cli.json && !cli.dry_run;
+        return init::handle_init(cli.force, interactive, cli.dry_run, cli.json);
+    }
+
+    if let Command::Config(ConfigCommand::Check) = command {
+        return config::handle_config_check(cli.profile.as_deref(), cli.json);
+    }
+
     // Load configuration
-    let config = Config::load().unwrap_or_else(|_| Config::default());
-    let cli_args = cli.to_cli_args();
+    // An unreadable config could drop the exclude list, so never fall back to defaults
+    let config = match Config::load(cli.profile.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: invalid configuration: {:#}", e);
+            eprintln!("Run `cachekill config check` after fixing it.");
+            process::exit(ci::exit_codes::CONFIG_ERROR);
+        }
+    };
+    let cli_args = cli.to_cli_args(&command);
     let merged_config = config.merge_with_cli(&cli_args);
 
     // Create output formatter
     let formatter = OutputFormatter::new(merged_config.json);
 
-    // Handle restore last backup
-    if merged_config.restore_last {
-        return handle_restore_last(&merged_config, &formatter);
-    }
-
-    // Handle doctor command
-    if cli.doctor {
-        return handle_doctor(&merged_config);
-    }
-
-    // Handle CI mode
-    if let Some(mode) = cli.ci {
-        let ci_mode = match mode.as_str() {
-            "prebuild" => CiMode::Prebuild,
-            "postbuild" => CiMode::Postbuild,
-            _ => {
-                eprintln!("Invalid CI mode: {}. Use 'prebuild' or 'postbuild'", mode);
-                process::exit(4);
+    // Overlapping runs would clean the same shared caches, whatever directory they start in
+    let _lock = if takes_lock(&command, &merged_config) {
+        match RunLock::acquire(&lock::state_dir(), !cli.no_wait) {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                process::exit(ci::exit_codes::LOCKED);
             }
-        };
-        return handle_ci_mode(&merged_config, ci_mode);
-    }
-
-    // Handle specialized integrations
-    if cli.hf {
-        if merged_config.list {
-            return handle_hf_list(&merged_config);
-        } else {
-            return handle_hf_clean(&merged_config, cli.model.as_deref());
         }
-    }
-
-    if cli.torch {
-        if merged_config.list {
-            return handle_torch_list(&merged_config);
-        } else {
-            return handle_torch_clean(&merged_config);
+    } else {
+        None
+    };
+
+    match command {
+        Command::List(_) => handle_list_command(&merged_config, &formatter),
+        // `clean --dry-run` only plans, like `plan`
+        Command::Clean(_) if merged_config.dry_run => {
+            handle_dry_run_mode(&merged_config, &formatter)
         }
-    }
-
-    if cli.vercel {
-        if merged_config.list {
-            return handle_vercel_status(&merged_config);
-        } else {
-            return handle_vercel_purge(&merged_config, cli.project.as_deref());
+        Command::Clean(_) => handle_cleanup_mode(&merged_config, &formatter),
+        Command::Plan(_) => handle_dry_run_mode(&merged_config, &formatter),
+        Command::Restore { backup } => handle_restore(&merged_config, &formatter, backup),
+        Command::Backups { remove_older_than } => {
+            handle_backups(&merged_config, &formatter, remove_older_than)
         }
-    }
-
-    if cli.cloudflare {
-        if merged_config.list {
-            return handle_cloudflare_status(&merged_config);
-        } else {
-            return handle_cloudflare_purge(&merged_config, cli.zone.as_deref());
+        Command::Doctor => handle_doctor(&merged_config),
+        Command::Verify => package_managers::handle_verify(&merged_config),
+        Command::Tag(_) => cachedir_tag::handle_tag_caches(&merged_config),
+        Command::Ci { mode } => handle_ci_mode(&merged_config, mode),
+        Command::Hf(HfCommand::List) => handle_hf_list(&merged_config),
+        Command::Hf(HfCommand::Clean { model }) => {
+            handle_hf_clean(&merged_config, model.as_deref())
         }
+        Command::Torch(TorchCommand::List) => handle_torch_list(&merged_config),
+        Command::Torch(TorchCommand::Clean) => handle_torch_clean(&merged_config),
+        Command::Edge(EdgeCommand::Status {
+            provider: EdgeProvider::Vercel,
+        }) => handle_vercel_status(&merged_config),
+        Command::Edge(EdgeCommand::Status {
+            provider: EdgeProvider::Cloudflare,
+        }) => handle_cloudflare_status(&merged_config),
+        Command::Edge(EdgeCommand::Purge(EdgePurge::Vercel { project, token })) => {
+            handle_vercel_purge(&merged_config, project.as_deref(), token.as_deref())
+        }
+        Command::Edge(EdgeCommand::Purge(EdgePurge::Cloudflare { zone, token })) => {
+            handle_cloudflare_purge(&merged_config, zone.as_deref(), token.as_deref())
+        }
+        Command::Scan(args) => handle_scan(&merged_config, &formatter, &args),
+        Command::Config(ConfigCommand::Show { origin }) => {
+            config::handle_config_show(&config, &cli_args, origin)
+        }
+        Command::Config(ConfigCommand::Check) | Command::Init => unreachable!("handled above"),
     }
+}
 
-    // Handle NPX list mode (must be before general list mode)
-    if merged_config.npx && merged_config.list {
-        return handle_npx_list_mode(&merged_config, &formatter);
-    }
-
-    // Handle Docker list mode (must be before general list mode)
-    if merged_config.docker && merged_config.list {
-        return handle_docker_list_mode(&merged_config, &formatter);
+/// `list`: project caches, or NPX packages / Docker usage with `--npx` / `--docker`
+fn handle_list_command(config: &MergedConfig, formatter: &OutputFormatter) -> Result<()> {
+    if config.npx && config.docker {
+        anyhow::bail!("list shows either --npx or --docker, not both");
     }
-
-    // Handle list mode
-    if merged_config.list {
-        return handle_list_mode(&merged_config, &formatter);
+    if config.npx {
+        return handle_npx_list_mode(config, formatter);
     }
-
-    // Handle dry run mode
-    if merged_config.dry_run {
-        return handle_dry_run_mode(&merged_config, &formatter);
+    if config.docker {
+        return handle_docker_list_mode(config, formatter);
     }
-
-    // Handle normal cleanup mode
-    handle_cleanup_mode(&merged_config, &formatter)
+    handle_list_mode(config, formatter)
 }
 
-fn handle_restore_last(config: &MergedConfig, formatter: &OutputFormatter) -> Result<()> {
+fn handle_restore(
+    config: &MergedConfig,
+    formatter: &OutputFormatter,
+    backup: Option<String>,
+) -> Result<()> {
     let executor = ActionExecutor::new(config.clone());
+    let result = match &backup {
+        Some(name) => executor.restore_backup(name),
+        None => executor.restore_last_backup(),
+    };
 
-    match executor.restore_last_backup() {
+    match result {
         Ok(result) => {
             if let Err(e) = formatter.print_restore_result(&result) {
                 eprintln!("Error printing restore result: {}", e);
@@ -316,6 +793,40 @@ fn handle_restore_last(config: &MergedConfig, formatter: &OutputFormatter) -> Re
     }
 }
 
+fn handle_backups(
+    config: &MergedConfig,
+    formatter: &OutputFormatter,
+    remove_older_than: Option<u32>,
+) -> Result<()> {
+    let executor = ActionExecutor::new(config.clone());
+    let removed = match remove_older_than {
+        Some(days) if config.dry_run => {
+            if !config.json {
+                println!("Dry run: would remove backups older than {} days", days);
+            }
+            None
+        }
+        Some(days) => Some(executor.clean_old_backups(days)?),
+        None => None,
+    };
+    let backups = executor.list_backups().unwrap_or_default();
+    if let Err(e) = formatter.print_backups(&backups, removed.as_ref()) {
+        eprintln!("Error printing backups: {}", e);
+    }
+    Ok(())
+}
+
+/// Show where each JS package manager cache was resolved from when `--js-pm` is set
+fn print_js_pm_locations(config: &MergedConfig, formatter: &OutputFormatter) {
+    if !config.js_pm {
+        return;
+    }
+    let locations = package_managers::PackageManagers::new(config.clone()).locations();
+    if let Err(e) = formatter.print_pm_locations(&locations) {
+        eprintln!("Error printing package manager locations: {}", e);
+    }
+}
+
 fn handle_list_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Result<()> {
     // Discover cache entries
     let discovery: DiscoveryResult = DiscoveryResult::discover(config)?;
@@ -332,6 +843,7 @@ fn handle_list_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Resul
         if !config.json {
             println!("No cache entries found.");
         }
+        print_js_pm_locations(config, formatter);
         return Ok(());
     }
 
@@ -339,6 +851,10 @@ fn handle_list_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Resul
     if let Err(e) = formatter.print_cache_table(&entries) {
         eprintln!("Error printing cache table: {}", e);
     }
+    if config.explain {
+        formatter.print_explanations(&entries);
+    }
+    print_js_pm_locations(config, formatter);
 
     // Print summary
     let summary = inspector.get_summary(&entries);
@@ -435,13 +951,26 @@ fn handle_docker_list_mode(config: &MergedConfig, formatter: &OutputFormatter) -
     Ok(())
 }
 
+/// Whether `command` changes caches or backups, and so must not overlap another run
+fn takes_lock(command: &Command, config: &MergedConfig) -> bool {
+    if config.dry_run {
+        return false;
+    }
+    match command {
+        Command::Clean(_)
+        | Command::Restore { .. }
+        | Command::Hf(HfCommand::Clean { .. })
+        | Command::Torch(TorchCommand::Clean) => true,
+        Command::Backups { remove_older_than } => remove_older_than.is_some(),
+        Command::Ci { mode } => matches!(mode, CiMode::Postbuild),
+        Command::Scan(args) => args.clean,
+        _ => false,
+    }
+}
+
 fn handle_dry_run_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Result<()> {
     // Discover and inspect cache entries
-    let discovery = DiscoveryResult::discover(config)?;
-    let inspector = CacheInspector::new(config.clone());
-    let entries = inspector.inspect_caches(&discovery.cache_entries)?;
-    // Add NPX cache if requested
-    let mut all_entries = entries;
+    let mut all_entries = discover_project_entries(config)?;
 
     // Add JS package manager caches when requested
     if let Err(e) = package_managers::add_js_pm_entries(&mut all_entries, config) {
@@ -463,23 +992,120 @@ fn handle_dry_run_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Re
         }
     }
 
-    // Execute dry run
+    add_budget_entries(config, &mut all_entries);
+
     let executor = ActionExecutor::new(config.clone());
+    let held = executor.protect_in_use(&mut all_entries);
+    formatter.print_held(&held);
+
+    if let Some(target) = config.free_target {
+        let plan = free_plan(&all_entries, target)?;
+        if let Err(e) = formatter.print_free_plan(&plan) {
+            eprintln!("Error printing free-space plan: {}", e);
+        }
+        return Ok(());
+    }
+
+    apply_cache_budget(config, formatter, &mut all_entries);
+
+    // Execute dry run
     let result = executor.dry_run(&all_entries)?;
 
     // Print results
     if let Err(e) = formatter.print_dry_run(&result) {
         eprintln!("Error printing dry run results: {}", e);
     }
+    if config.explain {
+        formatter.print_explanations(&all_entries);
+    }
+    if config.rehydrate.is_some() {
+        let removed: Vec<PathBuf> = result
+            .to_delete
+            .iter()
+            .chain(&result.to_backup)
+            .map(|e| e.path.clone())
+            .collect();
+        formatter.print_rehydrate_plan(&rehydrate::plan(&all_entries, &removed));
+    }
 
     Ok(())
 }
 
+/// Add the caches a `max_total_cache` budget counts whatever the selection flags say:
+/// global detector caches (models, `~/.m2`), package manager, NPX and Docker caches
+fn add_budget_entries(config: &MergedConfig, entries: &mut Vec<CacheEntry>) {
+    if config.max_total_cache.is_none() || config.free_target.is_some() {
+        return;
+    }
+
+    let known: HashSet<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+    let globals: Vec<_> = DiscoveryResult::discover_global(&DetectorRegistry::load(), config)
+        .into_iter()
+        .filter(|c| !known.contains(&c.path))
+        .collect();
+    match CacheInspector::new(config.clone()).inspect_caches(&globals) {
+        Ok(found) => entries.extend(found),
+        Err(e) => eprintln!("Warning: failed to inspect global caches: {e}"),
+    }
+
+    if !config.js_pm {
+        let all_pms = MergedConfig {
+            js_pm: true,
+            ..config.clone()
+        };
+        if let Err(e) = package_managers::add_js_pm_entries(entries, &all_pms) {
+            eprintln!("Warning: failed to add JS PM entries: {e}");
+        }
+    }
+    if !config.npx {
+        if let Ok(npx_entries) = NpxCacheManager::new(config.clone()).list_npx_cache() {
+            entries.extend(npx_entries);
+        }
+    }
+    if !config.docker {
+        if let Ok(docker_entries) = DockerCacheManager::new(config.clone()).list_docker_cache() {
+            entries.extend(docker_entries);
+        }
+    }
+}
+
+/// Evict least recently used entries past `max_total_cache`; a free-space target replaces it
+fn apply_cache_budget(
+    config: &MergedConfig,
+    formatter: &OutputFormatter,
+    entries: &mut [CacheEntry],
+) -> bool {
+    let Some(budget) = config
+        .max_total_cache
+        .filter(|_| config.free_target.is_none())
+    else {
+        return false;
+    };
+    let result = CacheInspector::new(config.clone()).apply_budget(entries, budget);
+    formatter.print_budget(&result);
+    true
+}
+
+/// Rank entries for a `--free` / `--until-free` target on the current directory's filesystem
+fn free_plan(entries: &[CacheEntry], target: FreeTarget) -> Result<FreePlan> {
+    let disk = DiskSpace::of(&util::get_current_dir()?)?;
+    Ok(FreePlan::new(entries, target, disk))
+}
+
+/// Discover and inspect project caches, unless the run only targets NPX packages
+fn discover_project_entries(config: &MergedConfig) -> Result<Vec<CacheEntry>> {
+    if config.npx_selection_only() {
+        return Ok(Vec::new());
+    }
+    let discovery = DiscoveryResult::discover(config)?;
+    let inspector = CacheInspector::new(config.clone());
+    inspector.inspect_caches(&discovery.cache_entries)
+}
+
 fn handle_cleanup_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Result<()> {
     // Discover and inspect cache entries
-    let discovery = DiscoveryResult::discover(config)?;
     let inspector = CacheInspector::new(config.clone());
-    let mut entries = inspector.inspect_caches(&discovery.cache_entries)?;
+    let mut entries = discover_project_entries(config)?;
 
     if let Err(e) = package_managers::add_js_pm_entries(&mut entries, config) {
         eprintln!("Warning: failed to add JS PM entries: {e}");
@@ -501,6 +1127,8 @@ fn handle_cleanup_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Re
         }
     }
 
+    add_budget_entries(config, &mut entries);
+
     if entries.is_empty() {
         if !config.json {
             println!("No cache entries found to clean.");
@@ -508,6 +1136,38 @@ fn handle_cleanup_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Re
         return Ok(());
     }
 
+    // Held caches must not count toward a target or budget; holders are only
+    // stopped after confirmation, by `execute_cleanup`
+    let planning = MergedConfig {
+        dry_run: true,
+        ..config.clone()
+    };
+    let held = ActionExecutor::new(planning).protect_in_use(&mut entries);
+    formatter.print_held(&held);
+
+    // A free-space target cleans its ranked entries, in order, after one confirmation
+    if let Some(target) = config.free_target {
+        let plan = free_plan(&entries, target)?;
+        if !config.json {
+            if let Err(e) = formatter.print_free_plan(&plan) {
+                eprintln!("Error printing free-space plan: {}", e);
+            }
+        }
+        if plan.steps.is_empty() {
+            return Ok(());
+        }
+        return confirm_and_execute(config, formatter, plan.entries());
+    }
+
+    // Within a budget only the evicted entries are cleaned, so nothing is left to pick
+    if apply_cache_budget(config, formatter, &mut entries) {
+        entries.retain(|e| !matches!(e.planned_action, Some(PlannedAction::Skip)));
+        if entries.is_empty() {
+            return Ok(());
+        }
+        return confirm_and_execute(config, formatter, entries);
+    }
+
     // Show summary
     let summary = inspector.get_summary(&entries);
     if !config.json {
@@ -531,6 +1191,64 @@ fn handle_cleanup_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Re
         }
     }
 
+    // On a terminal, let the user pick entries instead of confirming all of them
+    if !config.force && !config.json && std::io::stdin().is_terminal() {
+        return match picker::pick_entries(entries)? {
+            Some(picked) if !picked.is_empty() => execute_cleanup(config, formatter, picked),
+            Some(_) => {
+                println!("Nothing selected.");
+                Ok(())
+            }
+            None => {
+                println!("Operation cancelled.");
+                Ok(())
+            }
+        };
+    }
+
+    confirm_and_execute(config, formatter, entries)
+}
+
+fn handle_scan(config: &MergedConfig, formatter: &OutputFormatter, args: &ScanArgs) -> Result<()> {
+    let roots = if args.roots.is_empty() {
+        vec![util::get_current_dir()?]
+    } else {
+        args.roots.clone()
+    };
+    let projects = scan::scan(&roots, args.depth, config)?;
+    let inactive_days = args.inactive_months.map(|months| i64::from(months) * 30);
+
+    if let Err(e) = formatter.print_scan(&projects, inactive_days) {
+        eprintln!("Error printing scan results: {}", e);
+    }
+    let Some(days) = inactive_days.filter(|_| args.clean) else {
+        return Ok(());
+    };
+
+    let entries = scan::inactive_entries(&projects, days);
+    if entries.is_empty() {
+        if !config.json {
+            println!("No caches in projects inactive for {}+ days.", days);
+        }
+        return Ok(());
+    }
+
+    if config.dry_run {
+        let result = ActionExecutor::new(config.clone()).dry_run(&entries)?;
+        if let Err(e) = formatter.print_dry_run(&result) {
+            eprintln!("Error printing dry run results: {}", e);
+        }
+        return Ok(());
+    }
+    confirm_and_execute(config, formatter, entries)
+}
+
+/// Ask once (unless `--force`), then prune, back up or delete every entry
+fn confirm_and_execute(
+    config: &MergedConfig,
+    formatter: &OutputFormatter,
+    entries: Vec<CacheEntry>,
+) -> Result<()> {
     // Ask for confirmation unless forced
     if !config.force {
         let action = if config.safe_delete {
@@ -557,10 +1275,41 @@ fn handle_cleanup_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Re
         }
     }
 
-    // Execute cleanup
+    execute_cleanup(config, formatter, entries)
+}
+
+/// Prune, back up or delete every entry according to its planned action
+fn execute_cleanup(
+    config: &MergedConfig,
+    formatter: &OutputFormatter,
+    mut entries: Vec<CacheEntry>,
+) -> Result<()> {
     let executor = ActionExecutor::new(config.clone());
 
-    if config.safe_delete {
+    // A running build would be corrupted by deleting its cache underneath it
+    let held = executor.protect_in_use(&mut entries);
+    formatter.print_held(&held);
+
+    // Remember what `--rehydrate` could run before the entries are consumed
+    let rehydratable: Vec<CacheEntry> = match config.rehydrate {
+        Some(_) => entries
+            .iter()
+            .filter(|e| e.project_root.is_some() && e.rehydrate_command().is_some())
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    };
+
+    // Prune-planned entries are trimmed in place and never backed up
+    let prune_result = executor.prune(&entries)?;
+    if !prune_result.pruned.is_empty() || !prune_result.failed.is_empty() {
+        if let Err(e) = formatter.print_prune_result(&prune_result) {
+            eprintln!("Error printing prune results: {}", e);
+        }
+    }
+    let prune_failed = !prune_result.failed.is_empty();
+
+    let (cleaned, any_failures) = if config.safe_delete {
         // Partition entries into backup vs delete-only and perform both actions
         let (to_backup, to_delete): (Vec<_>, Vec<_>) = entries
             .into_iter()
@@ -578,7 +1327,8 @@ fn handle_cleanup_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Re
             eprintln!("Error printing hard delete results: {}", e);
         }
 
-        let any_failures = !backup_result.failed.is_empty() || !hard_result.failed.is_empty();
+        let any_failures =
+            !backup_result.failed.is_empty() || !hard_result.failed.is_empty() || prune_failed;
         if !config.json {
             if !any_failures {
                 println!(
@@ -590,22 +1340,58 @@ fn handle_cleanup_mode(config: &MergedConfig, formatter: &OutputFormatter) -> Re
                 println!("⚠️  Cleanup completed with some failures");
             }
         }
-        process::exit(if any_failures { 2 } else { 0 });
+        let mut cleaned: Vec<PathBuf> = backup_result
+            .backed_up
+            .into_iter()
+            .map(|b| b.original_path)
+            .collect();
+        cleaned.extend(hard_result.deleted);
+        (cleaned, any_failures)
     } else {
         // No safe-delete: just hard-delete all Delete-planned entries
         let result = executor.hard_delete(&entries)?;
         if let Err(e) = formatter.print_hard_delete_result(&result) {
             eprintln!("Error printing hard delete results: {}", e);
         }
+        let any_failures = !result.failed.is_empty() || prune_failed;
         if !config.json {
-            if result.failed.is_empty() {
+            if !any_failures {
                 println!("✅ Successfully deleted {} entries", result.deleted.len());
             } else {
                 println!("⚠️  Cleanup completed with some failures");
             }
         }
-        process::exit(if result.failed.is_empty() { 0 } else { 2 });
+        (result.deleted, any_failures)
+    };
+
+    let rehydrate_failed = match config.rehydrate {
+        Some(jobs) => rehydrate_projects(formatter, &rehydratable, &cleaned, jobs)?,
+        None => false,
+    };
+    process::exit(if any_failures || rehydrate_failed {
+        2
+    } else {
+        0
+    });
+}
+
+/// Run the regenerate command of every cleaned project; true when any of them failed
+fn rehydrate_projects(
+    formatter: &OutputFormatter,
+    entries: &[CacheEntry],
+    cleaned: &[PathBuf],
+    jobs: usize,
+) -> Result<bool> {
+    let tasks = rehydrate::plan(entries, cleaned);
+    if tasks.is_empty() {
+        return Ok(false);
+    }
+    formatter.print_rehydrate_start(&tasks, jobs);
+    let result = rehydrate::run(&tasks, jobs, &rehydrate::log_path())?;
+    if let Err(e) = formatter.print_rehydrate_result(&result) {
+        eprintln!("Error printing rehydrate results: {}", e);
     }
+    Ok(result.failed_count() > 0)
 }
 
 #[cfg(test)]
@@ -614,48 +1400,95 @@ mod tests {
 
     #[test]
     fn test_cli_parsing() {
-        let args = vec!["cachekill", "--list", "--json"];
+        let args = vec!["cachekill", "list", "--json"];
         let cli: Cli = Parser::try_parse_from(args).unwrap();
-        assert!(cli.list);
+        assert!(matches!(cli.command, Some(Command::List(_))));
         assert!(cli.json);
     }
 
     #[test]
-    fn test_cli_args_conversion() {
-        let cli = Cli {
-            list: true,
-            dry_run: false,
-            force: false,
-            yes: false,
-            json: true,
-            lang: Some("js".to_string()),
-            paths: Some("**/node_modules".to_string()),
-            exclude: None,
-            stale_days: Some(7),
-            safe_delete: Some(true),
-            backup_dir: Some(".backup".to_string()),
-            docker: false,
-            npx: false,
-            restore_last: false,
-            all: false,
-            doctor: false,
-            ci: None,
-            hf: false,
-            torch: false,
-            vercel: false,
-            cloudflare: false,
-            model: None,
-            project: None,
-            zone: None,
-            token: None,
-            js_pm: false,
-        };
+    fn test_legacy_flags_map_to_commands() {
+        let cli: Cli = Parser::try_parse_from(["cachekill", "--hf", "--model", "gpt2"]).unwrap();
+        assert!(matches!(
+            cli.resolve_command(),
+            Command::Hf(HfCommand::Clean { model: Some(m) }) if m == "gpt2"
+        ));
+
+        let cli: Cli = Parser::try_parse_from(["cachekill", "--cloudflare", "--list"]).unwrap();
+        assert!(matches!(
+            cli.resolve_command(),
+            Command::Edge(EdgeCommand::Status {
+                provider: EdgeProvider::Cloudflare
+            })
+        ));
+
+        let cli: Cli = Parser::try_parse_from(["cachekill", "--tag-caches"]).unwrap();
+        assert!(matches!(cli.resolve_command(), Command::Tag(_)));
+
+        let cli: Cli = Parser::try_parse_from(["cachekill"]).unwrap();
+        assert!(matches!(cli.resolve_command(), Command::Clean(_)));
+
+        // Combinations a legacy mode would silently ignore are refused
+        for args in [
+            &["cachekill", "--list", "--dry-run"][..],
+            &["cachekill", "--hf", "--docker", "--list"],
+            &["cachekill", "--token", "abc", "--list"],
+            &["cachekill", "--hf", "--torch"],
+            &["cachekill", "--verify", "--dry-run"],
+            &["cachekill", "--tag-caches", "--list"],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err(), "{:?}", args);
+        }
+        for args in [
+            &["cachekill", "--tag-caches", "list"][..],
+            &["cachekill", "--list", "plan"],
+        ] {
+            let cli = Cli::try_parse_from(args).unwrap();
+            assert!(cli.check_legacy().is_err(), "{:?}", args);
+        }
+        assert!(Cli::try_parse_from(["cachekill", "--list", "--docker"]).is_ok());
+        assert!(Cli::try_parse_from(["cachekill", "--vercel", "--token", "abc"]).is_ok());
+    }
 
-        let cli_args = cli.to_cli_args();
-        assert!(cli_args.list);
-        assert!(cli_args.json);
+    #[test]
+    fn test_cli_args_conversion() {
+        let cli: Cli = Parser::try_parse_from([
+            "cachekill",
+            "--stale-days",
+            "30",
+            "plan",
+            "--lang",
+            "js",
+            "--paths",
+            "**/node_modules",
+            "--stale-days",
+            "7",
+        ])
+        .unwrap();
+        let command = cli.resolve_command();
+
+        let cli_args = cli.to_cli_args(&command);
+        assert!(cli_args.dry_run);
         use crate::cache_entry::LanguageFilter;
         assert_eq!(cli_args.lang, Some(LanguageFilter::JavaScript));
+        assert_eq!(cli_args.paths, Some(vec!["**/node_modules".to_string()]));
         assert_eq!(cli_args.stale_days, Some(7));
     }
+
+    #[test]
+    fn test_subcommand_args_are_validated() {
+        assert!(Parser::try_parse_from(["cachekill", "ci", "nightly"])
+            .map(|_: Cli| ())
+            .is_err());
+        assert!(
+            Parser::try_parse_from(["cachekill", "list", "--package", "x"])
+                .map(|_: Cli| ())
+                .is_err()
+        );
+        assert!(
+            Parser::try_parse_from(["cachekill", "edge", "status", "fastly"])
+                .map(|_: Cli| ())
+                .is_err()
+        );
+    }
 }
@@ -7,6 +7,12 @@ use std::process::Command;
 /// Provides cache management tools through the Model Context Protocol
 pub struct CacheKillMcpServer;
 
+impl Default for CacheKillMcpServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CacheKillMcpServer {
     pub fn new() -> Self {
         Self
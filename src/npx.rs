@@ -1,11 +1,13 @@
 use crate::cache_entry::{CacheEntry, CacheKind, PlannedAction};
 use crate::config::MergedConfig;
-use crate::util::{get_most_recent_mtime, get_size, is_dir, path_exists};
+use crate::package_managers::npm::NpmManager;
+use crate::regen::{RegenCost, RegenSource};
+use crate::util::{get_most_recent_mtime, get_size, is_dir, matches_any_glob, path_exists};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use walkdir::WalkDir;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// NPX package entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,7 +22,6 @@ pub struct NpxPackage {
 
 /// Package information from package.json
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 struct PackageInfo {
     name: String,
     version: Option<String>,
@@ -39,12 +40,10 @@ impl NpxCacheManager {
 
     /// Get the NPX cache directory path for the current platform
     pub fn get_npx_cache_dir() -> Result<PathBuf> {
-        if let Some(home) = dirs::home_dir() {
-            let npx_cache = home.join(".npm").join("_npx");
-            Ok(npx_cache)
-        } else {
-            Err(anyhow::anyhow!("Could not determine home directory"))
-        }
+        // npx lives inside npm's cache, so it follows `npm_config_cache` and `.npmrc`
+        NpmManager::cache_location()
+            .map(|location| location.path.join("_npx"))
+            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))
     }
 
     /// Check if NPX cache exists
@@ -54,45 +53,73 @@ impl NpxCacheManager {
             .unwrap_or(false)
     }
 
-    /// List NPX cache entries
+    /// List NPX cache entries, one per `_npx/<hash>` package directory
     pub fn list_npx_cache(&self) -> Result<Vec<CacheEntry>> {
-        let npx_cache_dir = Self::get_npx_cache_dir()?;
-
-        if !path_exists(&npx_cache_dir) {
-            return Ok(vec![]);
-        }
-
-        let mut entries = Vec::new();
+        let packages = self.list_packages()?;
+        let selected = self.select_for_removal(&packages);
+
+        let entries = packages
+            .into_iter()
+            .map(|package| {
+                let action = if selected.contains(&package.path) {
+                    self.determine_planned_action()
+                } else {
+                    PlannedAction::Skip
+                };
+                CacheEntry::new(
+                    package.path,
+                    CacheKind::Npx,
+                    package.size_bytes,
+                    package.last_used,
+                    package.stale,
+                )
+                .with_planned_action(action)
+                .with_regen_cost(RegenCost::estimate(
+                    CacheKind::Npx,
+                    RegenSource::Network,
+                    package.size_bytes,
+                ))
+            })
+            .collect();
 
-        // Get the total size of the NPX cache directory
-        let total_size = get_size(&npx_cache_dir)?;
-        let last_used = get_most_recent_mtime(&npx_cache_dir)?;
-        let stale = self.is_stale(&last_used);
-
-        let entry = CacheEntry::new(npx_cache_dir, CacheKind::Npx, total_size, last_used, stale)
-            .with_planned_action(self.determine_planned_action());
-
-        entries.push(entry);
-
-        // Also list individual cached packages if they exist
-        if let Ok(read_dir) = std::fs::read_dir(&Self::get_npx_cache_dir()?) {
-            for entry in read_dir.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    let size = get_size(&path).unwrap_or(0);
-                    let last_used = get_most_recent_mtime(&path)
-                        .unwrap_or(DateTime::from_timestamp(0, 0).unwrap_or_default());
-                    let stale = self.is_stale(&last_used);
-
-                    let cache_entry = CacheEntry::new(path, CacheKind::Npx, size, last_used, stale)
-                        .with_planned_action(self.determine_planned_action());
+        Ok(entries)
+    }
 
-                    entries.push(cache_entry);
-                }
+    /// Pick the package directories to remove.
+    ///
+    /// `--package` narrows the candidates by name (glob patterns allowed). With
+    /// `--keep-versions N` everything but the N most recently used versions of each
+    /// package goes; otherwise named packages go entirely, and without a name
+    /// filter only stale packages are removed.
+    fn select_for_removal(&self, packages: &[NpxPackage]) -> HashSet<PathBuf> {
+        let names = &self.config.npx_packages;
+        let candidates: Vec<&NpxPackage> = packages
+            .iter()
+            .filter(|p| names.is_empty() || matches_any_glob(Path::new(&p.name), names))
+            .collect();
+
+        if let Some(keep) = self.config.keep_versions {
+            let mut by_name: HashMap<&str, Vec<&NpxPackage>> = HashMap::new();
+            for package in candidates {
+                by_name.entry(&package.name).or_default().push(package);
             }
+            by_name
+                .into_values()
+                .flat_map(|mut versions| {
+                    versions.sort_by_key(|p| std::cmp::Reverse(p.last_used));
+                    versions.into_iter().skip(keep)
+                })
+                .map(|p| p.path.clone())
+                .collect()
+        } else if !names.is_empty() {
+            candidates.into_iter().map(|p| p.path.clone()).collect()
+        } else {
+            candidates
+                .into_iter()
+                .filter(|p| p.stale)
+                .map(|p| p.path.clone())
+                .collect()
         }
-
-        Ok(entries)
     }
 
     /// Get NPX cache size
@@ -106,55 +133,6 @@ impl NpxCacheManager {
         }
     }
 
-    /// Clear NPX cache
-    #[allow(dead_code)]
-    pub fn clear_npx_cache(&self) -> Result<()> {
-        let npx_cache_dir = Self::get_npx_cache_dir()?;
-
-        if !path_exists(&npx_cache_dir) {
-            return Ok(());
-        }
-
-        if self.config.safe_delete {
-            self.safe_delete_npx_cache(&npx_cache_dir)?;
-        } else {
-            self.hard_delete_npx_cache(&npx_cache_dir)?;
-        }
-
-        Ok(())
-    }
-
-    /// Safe delete NPX cache (move to backup)
-    #[allow(dead_code)]
-    fn safe_delete_npx_cache(&self, npx_cache_dir: &PathBuf) -> Result<()> {
-        use crate::util::{create_backup_dir_name, get_backup_dir};
-        use fs_extra::dir;
-
-        let backup_dir = get_backup_dir();
-        let timestamped_backup = backup_dir.join(create_backup_dir_name());
-        let npx_backup = timestamped_backup.join("npx");
-
-        // Create backup directory
-        std::fs::create_dir_all(&npx_backup).context("Failed to create backup directory")?;
-
-        // Move NPX cache to backup
-        let options = dir::CopyOptions::new();
-        dir::move_dir(npx_cache_dir, &npx_backup, &options)
-            .context("Failed to move NPX cache to backup")?;
-
-        println!("✅ NPX cache moved to backup: {}", npx_backup.display());
-        Ok(())
-    }
-
-    /// Hard delete NPX cache
-    #[allow(dead_code)]
-    fn hard_delete_npx_cache(&self, npx_cache_dir: &PathBuf) -> Result<()> {
-        std::fs::remove_dir_all(npx_cache_dir).context("Failed to remove NPX cache directory")?;
-
-        println!("✅ NPX cache deleted");
-        Ok(())
-    }
-
     /// Check if NPX cache is stale
     fn is_stale(&self, last_used: &DateTime<Utc>) -> bool {
         let now = Utc::now();
@@ -230,43 +208,62 @@ impl NpxCacheManager {
             return Ok(vec![]);
         }
 
+        self.list_packages_in(&npx_cache_dir)
+    }
+
+    /// List the `<hash>` package directories inside an `_npx` directory
+    fn list_packages_in(&self, npx_cache_dir: &Path) -> Result<Vec<NpxPackage>> {
         let mut packages = Vec::new();
 
-        // Walk through NPX cache directory
-        for entry in WalkDir::new(&npx_cache_dir).max_depth(3) {
-            let entry = entry?;
+        for entry in std::fs::read_dir(npx_cache_dir)
+            .context("Failed to read NPX cache directory")?
+            .flatten()
+        {
             let path = entry.path();
-
-            if path.is_dir() {
-                // Try to find package.json in this directory
-                let package_json = path.join("package.json");
-                if path_exists(&package_json) {
-                    if let Ok(package_info) = self.parse_package_json(&package_json) {
-                        let size = get_size(path).unwrap_or(0);
-                        let last_used = get_most_recent_mtime(path).unwrap_or_else(|_| Utc::now());
-                        let stale = self.is_stale(&last_used);
-
-                        packages.push(NpxPackage {
-                            name: package_info.name,
-                            version: package_info.version,
-                            size_bytes: size,
-                            last_used,
-                            path: path.to_path_buf(),
-                            stale,
-                        });
-                    }
-                }
+            if !path.is_dir() {
+                continue;
             }
+
+            let package_info = self
+                .parse_package_json(&path.join("package.json"))
+                .unwrap_or_else(|_| PackageInfo {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    version: None,
+                });
+            let version = package_info
+                .version
+                .or_else(|| self.installed_version(&path, &package_info.name));
+
+            let size = get_size(&path).unwrap_or(0);
+            let last_used = get_most_recent_mtime(&path).unwrap_or_else(|_| Utc::now());
+            let stale = self.is_stale(&last_used);
+
+            packages.push(NpxPackage {
+                name: package_info.name,
+                version,
+                size_bytes: size,
+                last_used,
+                path,
+                stale,
+            });
         }
 
         // Sort by size descending
-        packages.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        packages.sort_by_key(|p| std::cmp::Reverse(p.size_bytes));
 
         Ok(packages)
     }
 
+    /// Resolve the installed version from `node_modules/<name>/package.json`
+    fn installed_version(&self, package_dir: &Path, name: &str) -> Option<String> {
+        let manifest = package_dir
+            .join("node_modules")
+            .join(name)
+            .join("package.json");
+        self.parse_package_json(&manifest).ok()?.version
+    }
+
     /// Parse package.json to extract name and version
-    #[allow(dead_code)]
     fn parse_package_json(&self, path: &std::path::Path) -> Result<PackageInfo> {
         let content = std::fs::read_to_string(path)?;
         let package: serde_json::Value = serde_json::from_str(&content)?;
@@ -300,7 +297,6 @@ mod tests {
 
     fn create_test_config() -> MergedConfig {
         MergedConfig {
-            list: false,
             dry_run: false,
             force: false,
             json: false,
@@ -312,9 +308,18 @@ mod tests {
             backup_dir: ".cachekill-backup".to_string(),
             docker: false,
             npx: false,
-            restore_last: false,
             all: false,
             js_pm: false,
+            npx_packages: vec![],
+            keep_versions: None,
+            gitignored: false,
+            min_size: crate::config::DEFAULT_MIN_SIZE,
+            rules: vec![],
+            explain: false,
+            free_target: None,
+            max_total_cache: None,
+            rehydrate: None,
+            kill_holders: false,
         }
     }
 
@@ -326,6 +331,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::overly_complex_bool_expr)]
     fn test_npx_cache_exists() {
         // This test might pass or fail depending on the system
         let exists = NpxCacheManager::npx_cache_exists();
@@ -334,6 +340,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_npx_manager_creation() {
         let config = create_test_config();
         let _manager = NpxCacheManager::new(config);
@@ -342,6 +349,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::overly_complex_bool_expr)]
     fn test_npx_availability() {
         // This test might pass or fail depending on the system
         let available = is_npx_available();
@@ -363,6 +371,85 @@ mod tests {
         assert!(manager.is_stale(&old_time));
     }
 
+    fn package(name: &str, days_ago: i64, stale: bool) -> NpxPackage {
+        NpxPackage {
+            name: name.to_string(),
+            version: None,
+            size_bytes: 100,
+            last_used: Utc::now() - chrono::Duration::days(days_ago),
+            path: PathBuf::from(format!("/npx/{}-{}", name, days_ago)),
+            stale,
+        }
+    }
+
+    #[test]
+    fn test_select_stale_packages_by_default() {
+        let manager = NpxCacheManager::new(create_test_config());
+        let packages = vec![package("cowsay", 1, false), package("cowsay", 30, true)];
+
+        let selected = manager.select_for_removal(&packages);
+        assert_eq!(selected.len(), 1);
+        assert!(selected.contains(&packages[1].path));
+    }
+
+    #[test]
+    fn test_select_packages_by_name() {
+        let mut config = create_test_config();
+        config.npx_packages = vec!["create-react-app".to_string()];
+        let manager = NpxCacheManager::new(config);
+        let packages = vec![
+            package("create-react-app", 1, false),
+            package("cowsay", 30, true),
+        ];
+
+        let selected = manager.select_for_removal(&packages);
+        assert_eq!(selected.len(), 1);
+        assert!(selected.contains(&packages[0].path));
+    }
+
+    #[test]
+    fn test_select_keeps_most_recent_versions() {
+        let mut config = create_test_config();
+        config.keep_versions = Some(1);
+        let manager = NpxCacheManager::new(config);
+        let packages = vec![
+            package("cowsay", 3, false),
+            package("cowsay", 1, false),
+            package("cowsay", 2, false),
+            package("prettier", 5, false),
+        ];
+
+        let selected = manager.select_for_removal(&packages);
+        assert_eq!(selected.len(), 2);
+        assert!(!selected.contains(&packages[1].path));
+        assert!(!selected.contains(&packages[3].path));
+    }
+
+    #[test]
+    fn test_list_packages_resolves_installed_version() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let hash_dir = temp_dir.path().join("0a1b2c3d");
+        let installed = hash_dir.join("node_modules").join("cowsay");
+        std::fs::create_dir_all(&installed).unwrap();
+        std::fs::write(
+            hash_dir.join("package.json"),
+            r#"{"dependencies":{"cowsay":"^1.5.0"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            installed.join("package.json"),
+            r#"{"name":"cowsay","version":"1.5.0"}"#,
+        )
+        .unwrap();
+
+        let manager = NpxCacheManager::new(create_test_config());
+        let packages = manager.list_packages_in(temp_dir.path()).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "cowsay");
+        assert_eq!(packages[0].version.as_deref(), Some("1.5.0"));
+        assert_eq!(packages[0].path, hash_dir);
+    }
+
     #[test]
     fn test_planned_action() {
         let mut config = create_test_config();
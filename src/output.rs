@@ -1,8 +1,15 @@
-use crate::actions::{DryRunResult, HardDeleteResult, RestoreResult, SafeDeleteResult};
-use crate::cache_entry::{CacheEntry, CacheKind, PlannedAction};
+use crate::actions::{
+    BackupInfo, CleanupResult, DryRunResult, HardDeleteResult, HeldEntry, PruneResult,
+    RestoreResult, SafeDeleteResult,
+};
+use crate::cache_entry::{CacheEntry, CacheKind};
 use crate::docker::DockerStats;
-use crate::inspect::CacheSummary;
+use crate::freespace::FreePlan;
+use crate::inspect::{BudgetResult, CacheSummary};
 use crate::npx::NpxStats;
+use crate::package_managers::PmLocation;
+use crate::rehydrate::{RehydrateResult, RehydrateTask};
+use crate::scan::ScannedProject;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -43,29 +50,80 @@ impl OutputFormatter {
         let mut size_width = 4; // "SIZE"
         let mut last_used_width = 9; // "LAST USED"
         let stale_width = 6; // "STALE?"
+        let mut reclaimable_width = 11; // "RECLAIMABLE"
+        let show_reclaimable = entries.iter().any(|e| e.reclaimable_bytes.is_some());
+        let confidence_width = 10; // "CONFIDENCE"
+        let show_confidence = entries.iter().any(|e| e.confidence.is_some());
+        let mut regen_width = 10; // "REGEN COST"
+        let show_regen = entries.iter().any(|e| e.regen_cost.is_some());
 
         for entry in entries {
             path_width = path_width.max(entry.path.to_string_lossy().len());
             kind_width = kind_width.max(entry.kind.to_string().len());
             size_width = size_width.max(entry.size_human().len());
             last_used_width = last_used_width.max(entry.last_used_human().len());
+            if let Some(reclaimable) = entry.reclaimable_human() {
+                reclaimable_width = reclaimable_width.max(reclaimable.len());
+            }
+            if let Some(cost) = &entry.regen_cost {
+                regen_width = regen_width.max(cost.describe().len());
+            }
         }
 
         // Print header
-        println!("{:<path_width$} | {:<kind_width$} | {:<size_width$} | {:<last_used_width$} | {:<stale_width$}", 
+        print!("{:<path_width$} | {:<kind_width$} | {:<size_width$} | {:<last_used_width$} | {:<stale_width$}", 
                  "PATH", "KIND", "SIZE", "LAST USED", "STALE?");
-        println!("{:-<path_width$}-+-{:-<kind_width$}-+-{:-<size_width$}-+-{:-<last_used_width$}-+-{:-<stale_width$}", 
+        if show_reclaimable {
+            print!(" | {:<reclaimable_width$}", "RECLAIMABLE");
+        }
+        if show_confidence {
+            print!(" | {:<confidence_width$}", "CONFIDENCE");
+        }
+        if show_regen {
+            print!(" | {:<regen_width$}", "REGEN COST");
+        }
+        println!();
+        print!("{:-<path_width$}-+-{:-<kind_width$}-+-{:-<size_width$}-+-{:-<last_used_width$}-+-{:-<stale_width$}", 
                  "", "", "", "", "");
+        if show_reclaimable {
+            print!("-+-{:-<reclaimable_width$}", "");
+        }
+        if show_confidence {
+            print!("-+-{:-<confidence_width$}", "");
+        }
+        if show_regen {
+            print!("-+-{:-<regen_width$}", "");
+        }
+        println!();
 
         // Print entries
         for entry in entries {
             let stale_str = if entry.stale { "Yes" } else { "No" };
-            println!("{:<path_width$} | {:<kind_width$} | {:<size_width$} | {:<last_used_width$} | {:<stale_width$}", 
+            print!("{:<path_width$} | {:<kind_width$} | {:<size_width$} | {:<last_used_width$} | {:<stale_width$}", 
                      entry.path.to_string_lossy(),
                      entry.kind.to_string(),
                      entry.size_human(),
                      entry.last_used_human(),
                      stale_str);
+            if show_reclaimable {
+                let reclaimable = entry.reclaimable_human().unwrap_or_else(|| "-".to_string());
+                print!(" | {:<reclaimable_width$}", reclaimable);
+            }
+            if show_confidence {
+                let confidence = entry
+                    .confidence
+                    .map(|c| c.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                print!(" | {:<confidence_width$}", confidence);
+            }
+            if show_regen {
+                let cost = entry
+                    .regen_cost
+                    .map(|c| c.describe())
+                    .unwrap_or_else(|| "-".to_string());
+                print!(" | {:<regen_width$}", cost);
+            }
+            println!();
         }
 
         Ok(())
@@ -110,6 +168,305 @@ impl OutputFormatter {
         Ok(())
     }
 
+    /// Print what decided each entry's action (`--explain`); JSON output carries `decided_by`
+    pub fn print_explanations(&self, entries: &[CacheEntry]) {
+        if self.json_mode || entries.is_empty() {
+            return;
+        }
+        println!("\n🧭 Decisions:");
+        for entry in entries {
+            let action = entry
+                .planned_action
+                .map(|action| action.to_string())
+                .unwrap_or_else(|| "none".to_string());
+            let found_by = entry
+                .detector
+                .as_deref()
+                .or(entry.manager.as_deref())
+                .map(|name| format!(", found by {}", name))
+                .unwrap_or_default();
+            println!(
+                "  {} → {} ({}{})",
+                entry.path.display(),
+                action,
+                entry.decided_by.as_deref().unwrap_or("default"),
+                found_by
+            );
+        }
+    }
+
+    /// Print resolved package manager cache locations and where each was configured
+    pub fn print_pm_locations(
+        &self,
+        locations: &[PmLocation],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.json_mode {
+            let output = JsonPmLocations {
+                package_managers: locations.to_vec(),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            println!("\n📍 Package manager locations:");
+            for location in locations {
+                let status = if location.exists { "✅" } else { "❌" };
+                println!(
+                    "  {} {}: {} {} (from {})",
+                    location.manager, location.label, status, location.path, location.source
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Print how far `max_total_cache` evicts; human output only
+    pub fn print_budget(&self, result: &BudgetResult) {
+        if self.json_mode {
+            return;
+        }
+        let size = |bytes: u64| humansize::format_size(bytes, humansize::DECIMAL);
+        println!(
+            "💰 Cache budget {}: {} in caches ({} pinned)",
+            size(result.budget),
+            size(result.total_size),
+            size(result.pinned_size)
+        );
+        if result.evicted_count == 0 {
+            println!("  Within budget; nothing to evict.");
+        } else {
+            println!(
+                "  Evicting {} least recently used entries ({}) → {}",
+                result.evicted_count,
+                size(result.evicted_size),
+                size(result.final_size)
+            );
+        }
+        if !result.met() {
+            println!(
+                "  ⚠️  Still {} over budget after evicting everything allowed",
+                size(result.final_size - result.budget)
+            );
+        }
+    }
+
+    /// Print caches running processes were using and what happened to them
+    pub fn print_held(&self, held: &[HeldEntry]) {
+        if self.json_mode || held.is_empty() {
+            return;
+        }
+        println!("\n🔒 Caches in use by running processes:");
+        for entry in held {
+            let holders = entry
+                .holders
+                .iter()
+                .map(|h| h.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let action = if entry.stopped {
+                "stopped"
+            } else if entry.skipped {
+                "skipped (--kill-holders stops them)"
+            } else {
+                "would be stopped"
+            };
+            println!("  {} ({}): {}", entry.path.display(), holders, action);
+        }
+    }
+
+    /// Print the regenerate commands `--rehydrate` would run after a cleanup
+    pub fn print_rehydrate_plan(&self, tasks: &[RehydrateTask]) {
+        if self.json_mode {
+            return;
+        }
+        if tasks.is_empty() {
+            println!("\n💧 Rehydrate: no cleaned project has a regenerate command");
+            return;
+        }
+        println!("\n💧 Would rehydrate {} projects:", tasks.len());
+        for task in tasks {
+            println!("  {} $ {}", task.project_root.display(), task.command);
+        }
+    }
+
+    /// Announce the regenerate commands about to run
+    pub fn print_rehydrate_start(&self, tasks: &[RehydrateTask], jobs: usize) {
+        if self.json_mode {
+            return;
+        }
+        println!(
+            "\n💧 Rehydrating {} projects ({} at a time)...",
+            tasks.len(),
+            jobs
+        );
+    }
+
+    /// Print which regenerate commands succeeded after a cleanup
+    pub fn print_rehydrate_result(
+        &self,
+        result: &RehydrateResult,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.json_mode {
+            let mut output = serde_json::to_value(result)?;
+            output["mode"] = "rehydrate".into();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        for outcome in &result.outcomes {
+            let status = match (outcome.success, outcome.exit_code, &outcome.error) {
+                (true, _, _) => "✅".to_string(),
+                (false, _, Some(error)) => format!("❌ {}", error),
+                (false, Some(code), _) => format!("❌ exit {}", code),
+                (false, None, None) => "❌ killed".to_string(),
+            };
+            println!(
+                "  {} {} $ {} ({:.1}s)",
+                status,
+                outcome.project_root.display(),
+                outcome.command,
+                outcome.duration_secs
+            );
+        }
+        println!(
+            "  Restored {} of {} projects; output in {}",
+            result.outcomes.len() - result.failed_count(),
+            result.outcomes.len(),
+            result.log_path.display()
+        );
+        Ok(())
+    }
+
+    /// Print the order a free-space target cleans entries in and where it stops
+    pub fn print_free_plan(&self, plan: &FreePlan) -> Result<(), Box<dyn std::error::Error>> {
+        if self.json_mode {
+            let mut output = serde_json::to_value(plan)?;
+            output["mode"] = "free-target".into();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        let size = |bytes: u64| humansize::format_size(bytes, humansize::DECIMAL);
+        println!(
+            "\n🎯 Target: {} on {} ({} of {} free)",
+            plan.target.describe(),
+            plan.disk.mount_point.display(),
+            size(plan.disk.available_bytes),
+            size(plan.disk.total_bytes)
+        );
+        if plan.needed_bytes == 0 {
+            println!("  Already met; nothing needs to be cleaned.");
+            return Ok(());
+        }
+        println!("  Needs: {}", size(plan.needed_bytes));
+
+        for (i, step) in plan.steps.iter().enumerate() {
+            println!(
+                "  {:>3}. {} ({}, {}) → {} total",
+                i + 1,
+                step.entry.path.display(),
+                step.entry.kind,
+                size(step.freed_bytes),
+                size(step.cumulative_bytes)
+            );
+        }
+        if plan.met {
+            println!(
+                "  ── stops here: {} freed, {} more candidates not needed",
+                size(plan.freed_bytes),
+                plan.not_needed.len()
+            );
+        } else {
+            println!(
+                "  ⚠️  All candidates together free only {} of the {} needed",
+                size(plan.freed_bytes),
+                size(plan.needed_bytes)
+            );
+        }
+        if plan.backups_ignored > 0 {
+            println!(
+                "  {} entries would only move to the backup directory on this filesystem; use --safe-delete false to count them",
+                plan.backups_ignored
+            );
+        }
+        if plan.elsewhere > 0 {
+            println!("  {} entries are on another filesystem", plan.elsewhere);
+        }
+        Ok(())
+    }
+
+    /// Print projects found by a scan, marking those inactive for `inactive_days`
+    pub fn print_scan(
+        &self,
+        projects: &[ScannedProject],
+        inactive_days: Option<i64>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let total_size: u64 = projects.iter().map(|p| p.size_bytes).sum();
+        let inactive: Vec<&ScannedProject> = projects
+            .iter()
+            .filter(|p| inactive_days.is_some_and(|days| p.days_inactive >= days))
+            .collect();
+        let inactive_size: u64 = inactive.iter().map(|p| p.size_bytes).sum();
+
+        if self.json_mode {
+            let output = JsonScan {
+                mode: "scan".to_string(),
+                projects: projects.to_vec(),
+                total_size_bytes: total_size,
+                total_size_human: humansize::format_size(total_size, humansize::DECIMAL),
+                inactive_days,
+                inactive_size_bytes: inactive_days.map(|_| inactive_size),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        if projects.is_empty() {
+            println!("No projects with caches found.");
+            return Ok(());
+        }
+
+        println!("🔭 Found {} projects with caches:", projects.len());
+        for project in projects {
+            let marker = if inactive.iter().any(|p| p.root == project.root) {
+                " 💤"
+            } else {
+                ""
+            };
+            println!(
+                "  {} ({}): {}, last touched {} days ago ({}){}",
+                project.root.display(),
+                project.detectors.join(", "),
+                project.size_human(),
+                project.days_inactive,
+                project.activity_signal,
+                marker
+            );
+            for cache in &project.caches {
+                let name = cache
+                    .path
+                    .strip_prefix(&project.root)
+                    .unwrap_or(&cache.path)
+                    .display()
+                    .to_string();
+                println!("      {} ({})", name, cache.size_human());
+            }
+        }
+
+        println!(
+            "\n📊 Total: {} across {} projects",
+            humansize::format_size(total_size, humansize::DECIMAL),
+            projects.len()
+        );
+        if let Some(days) = inactive_days {
+            println!(
+                "  💤 Inactive for {}+ days: {} projects, {}",
+                days,
+                inactive.len(),
+                humansize::format_size(inactive_size, humansize::DECIMAL)
+            );
+        }
+        Ok(())
+    }
+
     /// Print dry run results
     pub fn print_dry_run(&self, result: &DryRunResult) -> Result<(), Box<dyn std::error::Error>> {
         if self.json_mode {
@@ -118,6 +475,7 @@ impl OutputFormatter {
                 to_delete: result.to_delete.clone(),
                 to_backup: result.to_backup.clone(),
                 to_skip: result.to_skip.clone(),
+                to_prune: result.to_prune.clone(),
                 total_size_bytes: result.total_size,
                 total_size_human: result.total_size_human(),
                 total_count: result.total_count,
@@ -130,25 +488,65 @@ impl OutputFormatter {
             println!("  To delete: {}", result.to_delete.len());
             println!("  To backup: {}", result.to_backup.len());
             println!("  To skip: {}", result.to_skip.len());
+            if !result.to_prune.is_empty() {
+                println!("  To prune: {}", result.to_prune.len());
+            }
 
             if !result.to_delete.is_empty() {
                 println!("\n  🗑️  Will DELETE:");
                 for entry in &result.to_delete {
-                    println!("    {} ({})", entry.path.display(), entry.size_human());
+                    println!(
+                        "    {} ({}){}",
+                        entry.path.display(),
+                        entry.size_human(),
+                        regen_note(entry)
+                    );
                 }
             }
 
             if !result.to_backup.is_empty() {
                 println!("\n  📦 Will BACKUP:");
                 for entry in &result.to_backup {
-                    println!("    {} ({})", entry.path.display(), entry.size_human());
+                    println!(
+                        "    {} ({}){}",
+                        entry.path.display(),
+                        entry.size_human(),
+                        regen_note(entry)
+                    );
+                }
+            }
+
+            if !result.to_prune.is_empty() {
+                println!("\n  ✂️  Will PRUNE:");
+                for entry in &result.to_prune {
+                    let shared = entry
+                        .shared_human()
+                        .map(|shared| format!(", {} shared with projects", shared))
+                        .unwrap_or_default();
+                    println!(
+                        "    {} ({} of {}{})",
+                        entry.path.display(),
+                        entry
+                            .reclaimable_human()
+                            .unwrap_or_else(|| "0 B".to_string()),
+                        entry.size_human(),
+                        shared
+                    );
                 }
             }
 
             if !result.to_skip.is_empty() {
                 println!("\n  ⏭️  Will SKIP:");
                 for entry in &result.to_skip {
-                    println!("    {} ({})", entry.path.display(), entry.size_human());
+                    match &entry.skip_reason {
+                        Some(reason) => println!(
+                            "    {} ({}): {}",
+                            entry.path.display(),
+                            entry.size_human(),
+                            reason
+                        ),
+                        None => println!("    {} ({})", entry.path.display(), entry.size_human()),
+                    }
                 }
             }
         }
@@ -217,6 +615,7 @@ impl OutputFormatter {
                 failed: result.failed.clone(),
                 total_size_bytes: result.total_size,
                 total_size_human: humansize::format_size(result.total_size, humansize::DECIMAL),
+                native_commands: result.native_commands.clone(),
             };
             println!("{}", serde_json::to_string_pretty(&output)?);
         } else {
@@ -227,6 +626,9 @@ impl OutputFormatter {
             );
             println!("  Successfully deleted: {}", result.deleted.len());
             println!("  Failed: {}", result.failed.len());
+            for command in &result.native_commands {
+                println!("  Delegated to: {}", command);
+            }
 
             if !result.deleted.is_empty() {
                 println!("\n  ✅ Deleted:");
@@ -245,6 +647,54 @@ impl OutputFormatter {
         Ok(())
     }
 
+    /// Print prune results
+    pub fn print_prune_result(
+        &self,
+        result: &PruneResult,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.json_mode {
+            let output = JsonPrune {
+                mode: "prune".to_string(),
+                pruned: result.pruned.clone(),
+                failed: result.failed.clone(),
+                total_size_bytes: result.total_size,
+                total_size_human: humansize::format_size(result.total_size, humansize::DECIMAL),
+                native_commands: result.native_commands.clone(),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            println!("\n✂️  Prune Results:");
+            println!(
+                "  Total size freed: {}",
+                humansize::format_size(result.total_size, humansize::DECIMAL)
+            );
+            println!("  Successfully pruned: {}", result.pruned.len());
+            println!("  Failed: {}", result.failed.len());
+            for command in &result.native_commands {
+                println!("  Delegated to: {}", command);
+            }
+
+            if !result.pruned.is_empty() {
+                println!("\n  ✅ Pruned:");
+                for entry in &result.pruned {
+                    println!(
+                        "    {} ({})",
+                        entry.path.display(),
+                        humansize::format_size(entry.freed, humansize::DECIMAL)
+                    );
+                }
+            }
+
+            if !result.failed.is_empty() {
+                println!("\n  ❌ Failed:");
+                for entry in &result.failed {
+                    println!("    {}: {}", entry.path.display(), entry.error);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Print restore results
     pub fn print_restore_result(
         &self,
@@ -285,6 +735,51 @@ impl OutputFormatter {
         Ok(())
     }
 
+    /// Print backups made by safe delete, and any removed with `--remove-older-than`
+    pub fn print_backups(
+        &self,
+        backups: &[BackupInfo],
+        removed: Option<&CleanupResult>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.json_mode {
+            let output = serde_json::json!({
+                "backups": backups,
+                "removed": removed,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        if let Some(removed) = removed {
+            println!(
+                "🧹 Removed {} old backups ({})",
+                removed.removed.len(),
+                humansize::format_size(removed.total_freed, humansize::DECIMAL)
+            );
+        }
+        if backups.is_empty() {
+            println!("No backups found.");
+            return Ok(());
+        }
+        println!("\n📦 Backups (newest first):");
+        println!(
+            "{:<24} | {:<16} | {:>7} | {:>10}",
+            "Name", "Created", "Entries", "Size"
+        );
+        println!("{:-<24} | {:-<16} | {:->7} | {:->10}", "", "", "", "");
+        for backup in backups {
+            println!(
+                "{:<24} | {:<16} | {:>7} | {:>10}",
+                backup.name,
+                backup.created.format("%Y-%m-%d %H:%M"),
+                backup.entries,
+                backup.size_human()
+            );
+        }
+        println!("\nRestore one with `cachekill restore <NAME>`.");
+        Ok(())
+    }
+
     /// Print NPX cache information
     pub fn print_npx_info(&self, stats: &NpxStats) -> Result<(), Box<dyn std::error::Error>> {
         if self.json_mode {
@@ -353,16 +848,7 @@ impl OutputFormatter {
     fn calculate_totals(&self, entries: &[CacheEntry]) -> JsonTotals {
         let total_size: u64 = entries.iter().map(|e| e.size_bytes).sum();
         let count = entries.len();
-        let freed_bytes: u64 = entries
-            .iter()
-            .filter(|e| {
-                matches!(
-                    e.planned_action,
-                    Some(PlannedAction::Delete) | Some(PlannedAction::Backup)
-                )
-            })
-            .map(|e| e.size_bytes)
-            .sum();
+        let freed_bytes: u64 = entries.iter().map(|e| e.freed_bytes()).sum();
 
         JsonTotals {
             size_bytes: total_size,
@@ -391,12 +877,31 @@ struct JsonSummary {
 }
 
 /// JSON dry run structure
+#[derive(Serialize, Deserialize)]
+struct JsonPmLocations {
+    package_managers: Vec<PmLocation>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonScan {
+    mode: String,
+    projects: Vec<ScannedProject>,
+    total_size_bytes: u64,
+    total_size_human: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inactive_days: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inactive_size_bytes: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct JsonDryRun {
     mode: String,
     to_delete: Vec<CacheEntry>,
     to_backup: Vec<CacheEntry>,
     to_skip: Vec<CacheEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    to_prune: Vec<CacheEntry>,
     total_size_bytes: u64,
     total_size_human: String,
     total_count: usize,
@@ -421,6 +926,20 @@ struct JsonHardDelete {
     failed: Vec<crate::actions::FailedEntry>,
     total_size_bytes: u64,
     total_size_human: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    native_commands: Vec<String>,
+}
+
+/// JSON prune structure
+#[derive(Serialize, Deserialize)]
+struct JsonPrune {
+    mode: String,
+    pruned: Vec<crate::actions::PrunedEntry>,
+    failed: Vec<crate::actions::FailedEntry>,
+    total_size_bytes: u64,
+    total_size_human: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    native_commands: Vec<String>,
 }
 
 /// JSON restore structure
@@ -462,6 +981,18 @@ struct JsonTotals {
     freed_bytes: u64,
 }
 
+/// ` — 5 MB download to regenerate with `npm ci``, for dry-run lines
+fn regen_note(entry: &CacheEntry) -> String {
+    match (&entry.regen_cost, &entry.regenerate) {
+        (Some(cost), Some(command)) => {
+            format!(" — {} to regenerate with `{}`", cost.describe(), command)
+        }
+        (Some(cost), None) => format!(" — {} to regenerate", cost.describe()),
+        (None, Some(command)) => format!(" — regenerate with `{}`", command),
+        (None, None) => String::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::cache_entry::CacheEntry;
+use crate::config::MergedConfig;
+
+use super::common::{env_value, existing_dir, make_resolved_entry, ConfigSource, ResolvedDir};
+use super::traits::{CacheManager, CleanMode};
+
+pub struct BunManager {
+    pub(crate) config: MergedConfig,
+}
+
+impl BunManager {
+    pub fn new(config: MergedConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// Resolve the install cache: `BUN_INSTALL_CACHE_DIR` > `$BUN_INSTALL/install/cache` > default
+pub fn install_cache_location() -> Option<ResolvedDir> {
+    let (path, source) = env_value(&["BUN_INSTALL_CACHE_DIR"])
+        .map(|(name, dir)| (PathBuf::from(dir), ConfigSource::Env(name)))
+        .or_else(|| {
+            env_value(&["BUN_INSTALL"]).map(|(name, dir)| {
+                (
+                    PathBuf::from(dir).join("install").join("cache"),
+                    ConfigSource::Derived(format!("${}/install/cache", name)),
+                )
+            })
+        })
+        .or_else(|| {
+            dirs::home_dir().map(|home| {
+                (
+                    home.join(".bun").join("install").join("cache"),
+                    ConfigSource::Default,
+                )
+            })
+        })?;
+    Some(ResolvedDir::new("install cache", path, source))
+}
+
+impl CacheManager for BunManager {
+    fn name(&self) -> &'static str {
+        "bun"
+    }
+
+    fn locations(&self) -> Vec<ResolvedDir> {
+        install_cache_location().into_iter().collect()
+    }
+
+    fn binary(&self) -> Option<&'static str> {
+        Some("bun")
+    }
+
+    fn native_clean_args(&self, _entry: &CacheEntry, mode: CleanMode) -> Option<Vec<&'static str>> {
+        (mode == CleanMode::Delete).then(|| vec!["pm", "cache", "rm"])
+    }
+
+    fn list(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        for location in self.locations() {
+            if existing_dir(&location.path) {
+                entries.push(make_resolved_entry(&location, &self.config)?);
+            }
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,360 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/*
+ * npm's `_cacache` is a content-addressable store:
+ *   index-v5/<aa>/<bb>/<rest>     append-only buckets, one `<sha1>\t<json>` line per write
+ *   content-v2/<algo>/<aa>/<bb>/<rest>  blobs named after the hex digest of their integrity
+ * The last line for a key wins; a `null` integrity marks the key as removed.
+ */
+const INDEX_DIR: &str = "index-v5";
+const CONTENT_DIR: &str = "content-v2";
+
+/// Index line payload (only the fields needed for pruning)
+#[derive(Debug, Clone, Deserialize)]
+struct IndexEntry {
+    key: String,
+    integrity: Option<String>,
+    time: i64,
+}
+
+/// Planned rewrite of a single index bucket
+#[derive(Debug, Clone)]
+struct BucketRewrite {
+    path: PathBuf,
+    /// Raw lines to keep, verbatim so npm's own line hashes stay valid
+    keep: Vec<String>,
+    /// Bytes freed by rewriting (or removing) the bucket
+    freed: u64,
+}
+
+/// What pruning a cacache directory would remove
+#[derive(Debug, Clone, Default)]
+pub struct CacachePrunePlan {
+    /// Keys whose newest index entry is older than the cutoff
+    pub stale_keys: usize,
+    /// Keys that are kept
+    pub live_keys: usize,
+    /// Content blobs no kept index entry references
+    pub orphaned_content: Vec<PathBuf>,
+    /// Bytes freed by removing stale index lines and orphaned blobs
+    pub reclaimable_bytes: u64,
+    /// Newest index entry time across all keys
+    pub last_used: Option<DateTime<Utc>>,
+    buckets: Vec<BucketRewrite>,
+}
+
+/// Check whether a directory looks like an npm cacache root
+pub fn is_cacache_dir(path: &Path) -> bool {
+    path.join(INDEX_DIR).is_dir()
+}
+
+/// Analyze a cacache directory, treating keys last written before `cutoff` as stale
+pub fn plan_prune(root: &Path, cutoff: DateTime<Utc>) -> Result<CacachePrunePlan> {
+    let mut plan = CacachePrunePlan::default();
+    let mut referenced: HashSet<PathBuf> = HashSet::new();
+    let cutoff_ms = cutoff.timestamp_millis();
+
+    let index_dir = root.join(INDEX_DIR);
+    if index_dir.is_dir() {
+        for entry in WalkDir::new(&index_dir).into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let bucket_path = entry.path();
+            let content = fs::read_to_string(bucket_path).with_context(|| {
+                format!("Failed to read index bucket {}", bucket_path.display())
+            })?;
+
+            let lines = parse_bucket(&content);
+            // Last write for each key decides whether the key is kept
+            let mut latest: HashMap<&str, &IndexEntry> = HashMap::new();
+            for (_, parsed) in &lines {
+                latest.insert(parsed.key.as_str(), parsed);
+            }
+
+            let mut live: HashSet<&str> = HashSet::new();
+            for (key, newest) in &latest {
+                let time = DateTime::from_timestamp_millis(newest.time);
+                if let Some(time) = time {
+                    if plan.last_used.map_or(true, |t| time > t) {
+                        plan.last_used = Some(time);
+                    }
+                }
+                match &newest.integrity {
+                    Some(integrity) if newest.time >= cutoff_ms => {
+                        live.insert(key);
+                        referenced.extend(integrity_to_paths(root, integrity));
+                    }
+                    _ => plan.stale_keys += 1,
+                }
+            }
+            plan.live_keys += live.len();
+
+            let keep: Vec<String> = lines
+                .iter()
+                .filter(|(_, parsed)| live.contains(parsed.key.as_str()))
+                .map(|(raw, _)| raw.to_string())
+                .collect();
+
+            if keep.len() != lines.len() || lines.is_empty() {
+                let old_size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                let new_size = if keep.is_empty() {
+                    0
+                } else {
+                    keep.iter().map(|l| l.len() as u64 + 1).sum()
+                };
+                plan.buckets.push(BucketRewrite {
+                    path: bucket_path.to_path_buf(),
+                    keep,
+                    freed: old_size.saturating_sub(new_size),
+                });
+            }
+        }
+    }
+
+    let content_dir = root.join(CONTENT_DIR);
+    if content_dir.is_dir() {
+        for entry in WalkDir::new(&content_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if entry.file_type().is_file() && !referenced.contains(entry.path()) {
+                plan.reclaimable_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                plan.orphaned_content.push(entry.path().to_path_buf());
+            }
+        }
+    }
+
+    plan.reclaimable_bytes += plan.buckets.iter().map(|b| b.freed).sum::<u64>();
+    Ok(plan)
+}
+
+/// Remove stale index lines and the content blobs they no longer reference.
+/// Returns the number of bytes freed.
+pub fn prune(root: &Path, cutoff: DateTime<Utc>) -> Result<u64> {
+    let plan = plan_prune(root, cutoff)?;
+    let mut freed = 0u64;
+
+    for bucket in &plan.buckets {
+        if bucket.keep.is_empty() {
+            fs::remove_file(&bucket.path).with_context(|| {
+                format!("Failed to remove index bucket {}", bucket.path.display())
+            })?;
+        } else {
+            let mut content = bucket.keep.join("\n");
+            content.push('\n');
+            fs::write(&bucket.path, content).with_context(|| {
+                format!("Failed to rewrite index bucket {}", bucket.path.display())
+            })?;
+        }
+        freed += bucket.freed;
+    }
+
+    for blob in &plan.orphaned_content {
+        let size = fs::metadata(blob).map(|m| m.len()).unwrap_or(0);
+        fs::remove_file(blob)
+            .with_context(|| format!("Failed to remove content blob {}", blob.display()))?;
+        freed += size;
+    }
+
+    Ok(freed)
+}
+
+/// Parse bucket lines into `(raw line, entry)` pairs, skipping corrupt lines
+fn parse_bucket(content: &str) -> Vec<(&str, IndexEntry)> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let (_, json) = line.split_once('\t')?;
+            let parsed: IndexEntry = serde_json::from_str(json).ok()?;
+            Some((line, parsed))
+        })
+        .collect()
+}
+
+/// Map an SRI integrity string (`sha512-<base64> ...`) to content blob paths
+fn integrity_to_paths(root: &Path, integrity: &str) -> Vec<PathBuf> {
+    integrity
+        .split_whitespace()
+        .filter_map(|token| {
+            let token = token.split('?').next()?;
+            let (algo, digest) = token.split_once('-')?;
+            let hex: String = decode_base64(digest)?
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            if hex.len() < 5 {
+                return None;
+            }
+            Some(
+                root.join(CONTENT_DIR)
+                    .join(algo)
+                    .join(&hex[0..2])
+                    .join(&hex[2..4])
+                    .join(&hex[4..]),
+            )
+        })
+        .collect()
+}
+
+/// Minimal standard-alphabet base64 decoder for SRI digests
+pub(super) fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+            b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let bytes = input.trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(bytes.len() * 3 / 4);
+    for chunk in bytes.chunks(4) {
+        let mut acc = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            acc |= value(c)? << (18 - 6 * i);
+        }
+        let produced = match chunk.len() {
+            4 => 3,
+            3 => 2,
+            2 => 1,
+            _ => return None,
+        };
+        for i in 0..produced {
+            out.push((acc >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // Blob contents are never hashed, so any digest works as long as the paths line up
+    const FRESH_INTEGRITY: &str = "sha512-AAECAwQFBgc=";
+    const STALE_INTEGRITY: &str = "sha512-CAkKCwwNDg8=";
+
+    fn write_bucket(root: &Path, name: &str, key: &str, integrity: &str, time: i64) {
+        let dir = root.join(INDEX_DIR).join("aa").join("bb");
+        fs::create_dir_all(&dir).unwrap();
+        let json = format!(
+            r#"{{"key":"{}","integrity":"{}","time":{},"size":5,"metadata":{{}}}}"#,
+            key, integrity, time
+        );
+        fs::write(dir.join(name), format!("deadbeef\t{}\n", json)).unwrap();
+    }
+
+    fn write_blob(root: &Path, integrity: &str) -> PathBuf {
+        let path = integrity_to_paths(root, integrity).remove(0);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "hello").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(
+            decode_base64("AAECAwQFBgc=").unwrap(),
+            vec![0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        assert!(decode_base64("!!").is_none());
+    }
+
+    #[test]
+    fn test_integrity_to_paths() {
+        let root = Path::new("/cache");
+        let paths = integrity_to_paths(root, FRESH_INTEGRITY);
+        assert_eq!(
+            paths,
+            vec![root
+                .join(CONTENT_DIR)
+                .join("sha512")
+                .join("00")
+                .join("01")
+                .join("020304050607")]
+        );
+    }
+
+    #[test]
+    fn test_prune_removes_stale_entries_and_blobs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let now = Utc::now();
+        let old = now - chrono::Duration::days(30);
+
+        write_bucket(
+            root,
+            "fresh",
+            "make-fetch-happen:fresh",
+            FRESH_INTEGRITY,
+            now.timestamp_millis(),
+        );
+        write_bucket(
+            root,
+            "stale",
+            "make-fetch-happen:stale",
+            STALE_INTEGRITY,
+            old.timestamp_millis(),
+        );
+        let fresh_blob = write_blob(root, FRESH_INTEGRITY);
+        let stale_blob = write_blob(root, STALE_INTEGRITY);
+
+        let cutoff = now - chrono::Duration::days(14);
+        let plan = plan_prune(root, cutoff).unwrap();
+        assert_eq!(plan.stale_keys, 1);
+        assert_eq!(plan.live_keys, 1);
+        assert_eq!(plan.orphaned_content, vec![stale_blob.clone()]);
+        assert!(plan.reclaimable_bytes >= 5);
+
+        let freed = prune(root, cutoff).unwrap();
+        assert_eq!(freed, plan.reclaimable_bytes);
+        assert!(fresh_blob.exists());
+        assert!(!stale_blob.exists());
+        assert!(root
+            .join(INDEX_DIR)
+            .join("aa")
+            .join("bb")
+            .join("fresh")
+            .exists());
+        assert!(!root
+            .join(INDEX_DIR)
+            .join("aa")
+            .join("bb")
+            .join("stale")
+            .exists());
+    }
+
+    #[test]
+    fn test_deleted_key_is_stale() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let dir = root.join(INDEX_DIR).join("aa").join("bb");
+        fs::create_dir_all(&dir).unwrap();
+        let now = Utc::now().timestamp_millis();
+        fs::write(
+            dir.join("key"),
+            format!(
+                "h1\t{{\"key\":\"k\",\"integrity\":\"{}\",\"time\":{}}}\nh2\t{{\"key\":\"k\",\"integrity\":null,\"time\":{}}}\n",
+                FRESH_INTEGRITY, now, now
+            ),
+        )
+        .unwrap();
+
+        let plan = plan_prune(root, Utc::now() - chrono::Duration::days(14)).unwrap();
+        assert_eq!(plan.stale_keys, 1);
+        assert_eq!(plan.live_keys, 0);
+    }
+}
@@ -1,10 +1,11 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
 
+use super::{cacache, pnpm_store};
 use crate::cache_entry::{CacheEntry, CacheKind, PlannedAction};
 use crate::config::MergedConfig;
-use crate::util::{get_most_recent_mtime, get_size, is_dir, path_exists};
+use crate::util::{expand_home, get_most_recent_mtime, get_size, is_dir, path_exists};
 
 pub fn planned_action(_config: &MergedConfig) -> PlannedAction {
     /*
@@ -28,3 +29,306 @@ pub fn make_entry(dir: PathBuf, config: &MergedConfig) -> Result<CacheEntry> {
 pub fn existing_dir(p: &Path) -> bool {
     path_exists(p) && is_dir(p)
 }
+
+/// Remove a cache directory (or file) from disk
+pub fn delete_dir(path: &Path) -> Result<()> {
+    if !path_exists(path) {
+        return Ok(());
+    }
+    if is_dir(path) {
+        std::fs::remove_dir_all(path).context("Failed to remove directory")?;
+    } else {
+        std::fs::remove_file(path).context("Failed to remove file")?;
+    }
+    Ok(())
+}
+
+/// Trim a cache in place using the strategy for its layout, returning the bytes freed
+pub fn prune_dir(path: &Path, stale_days: u32) -> Result<u64> {
+    let cutoff = Utc::now() - chrono::Duration::days(stale_days as i64);
+
+    if cacache::is_cacache_dir(path) {
+        return cacache::prune(path, cutoff);
+    }
+    if pnpm_store::is_pnpm_store(path) {
+        return pnpm_store::prune(path);
+    }
+
+    Err(anyhow::anyhow!(
+        "No prune strategy for cache: {}",
+        path.display()
+    ))
+}
+
+/// Where a package manager cache location was taken from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Environment variable
+    Env(String),
+    /// Key in a config file (`.npmrc`, `.yarnrc.yml`)
+    File(PathBuf, String),
+    /// Derived from another location, e.g. `$PNPM_HOME/store`
+    Derived(String),
+    /// Built-in platform default
+    Default,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Env(name) => write!(f, "env {}", name),
+            ConfigSource::File(path, key) => write!(f, "{} ({})", path.display(), key),
+            ConfigSource::Derived(from) => write!(f, "{}", from),
+            ConfigSource::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// A resolved cache location and the source that won
+#[derive(Debug, Clone)]
+pub struct ResolvedDir {
+    /// What this location holds (e.g. "cache", "store", "project cache")
+    pub label: &'static str,
+    pub path: PathBuf,
+    pub source: ConfigSource,
+}
+
+impl ResolvedDir {
+    pub fn new(label: &'static str, path: PathBuf, source: ConfigSource) -> Self {
+        Self {
+            label,
+            path,
+            source,
+        }
+    }
+}
+
+/// Build a cache entry for a resolved location, recording where it came from
+pub fn make_resolved_entry(dir: &ResolvedDir, config: &MergedConfig) -> Result<CacheEntry> {
+    Ok(make_entry(dir.path.clone(), config)?.with_config_source(dir.source.to_string()))
+}
+
+/// First set environment variable among `names`
+pub fn env_value(names: &[&str]) -> Option<(String, String)> {
+    names.iter().find_map(|name| {
+        std::env::var(name)
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| (name.to_string(), v))
+    })
+}
+
+/// Resolve an npm-style config key from the environment (`npm_config_<key>`)
+pub fn npm_env_value(key: &str) -> Option<(PathBuf, ConfigSource)> {
+    let snake = key.replace('-', "_");
+    let lower = format!("npm_config_{}", snake);
+    let upper = lower.to_uppercase();
+    env_value(&[&lower, &upper])
+        .map(|(name, value)| (expand_config_path(&value, None), ConfigSource::Env(name)))
+}
+
+/// Nearest directory holding `package.json`, walking up from `start` the way npm and
+/// Yarn find the project; `start` itself when there is none
+fn project_root_from(start: &Path) -> PathBuf {
+    start
+        .ancestors()
+        .find(|dir| dir.join("package.json").exists())
+        .unwrap_or(start)
+        .to_path_buf()
+}
+
+/// Project root of the current directory, where project `.npmrc` / `.yarnrc.yml` live
+fn project_root() -> Option<PathBuf> {
+    std::env::current_dir()
+        .ok()
+        .map(|cwd| project_root_from(&cwd))
+}
+
+/// `.npmrc` files in npm's precedence order: project, then user
+pub fn npmrc_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Some(root) = project_root() {
+        files.push(root.join(".npmrc"));
+    }
+    match env_value(&["npm_config_userconfig", "NPM_CONFIG_USERCONFIG"]) {
+        Some((_, user)) => files.push(expand_home(&user)),
+        None => {
+            if let Some(home) = dirs::home_dir() {
+                files.push(home.join(".npmrc"));
+            }
+        }
+    }
+    files
+}
+
+/// Resolve `key` from the first `.npmrc` that sets it
+pub fn npmrc_value(key: &str) -> Option<(PathBuf, ConfigSource)> {
+    npmrc_files().into_iter().find_map(|file| {
+        let content = std::fs::read_to_string(&file).ok()?;
+        let value = parse_npmrc(&content, key)?;
+        let base = file.parent().map(Path::to_path_buf);
+        Some((
+            expand_config_path(&value, base.as_deref()),
+            ConfigSource::File(file, key.to_string()),
+        ))
+    })
+}
+
+/// `.yarnrc.yml` files in Yarn Berry's precedence order: project, then home
+pub fn yarnrc_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Some(root) = project_root() {
+        files.push(root.join(".yarnrc.yml"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        files.push(home.join(".yarnrc.yml"));
+    }
+    files
+}
+
+/// Resolve `key` from the first `.yarnrc.yml` that sets it
+pub fn yarnrc_value(key: &str) -> Option<(PathBuf, ConfigSource)> {
+    yarnrc_files().into_iter().find_map(|file| {
+        let content = std::fs::read_to_string(&file).ok()?;
+        let value = parse_yarnrc_yml(&content, key)?;
+        let base = file.parent().map(Path::to_path_buf);
+        Some((
+            expand_config_path(&value, base.as_deref()),
+            ConfigSource::File(file, key.to_string()),
+        ))
+    })
+}
+
+/// Read `key = value` from `.npmrc` (ini) content; the last assignment wins
+pub fn parse_npmrc(content: &str, key: &str) -> Option<String> {
+    content
+        .lines()
+        .rev()
+        .map(str::trim)
+        .filter(|line| !line.starts_with(';') && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| expand_env_refs(unquote(v.trim())))
+        .filter(|v| !v.is_empty())
+}
+
+/// Read a top-level `key: value` from `.yarnrc.yml` content
+pub fn parse_yarnrc_yml(content: &str, key: &str) -> Option<String> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace) && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .find(|(k, _)| unquote(k.trim()) == key)
+        .map(|(_, v)| {
+            let v = v.split(" #").next().unwrap_or(v);
+            expand_env_refs(unquote(v.trim()))
+        })
+        .filter(|v| !v.is_empty())
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+/// Expand `${VAR}` references the way npm and yarn do
+fn expand_env_refs(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        match rest[start + 2..].find('}') {
+            Some(end) => {
+                let name = &rest[start + 2..start + 2 + end];
+                out.push_str(&std::env::var(name).unwrap_or_default());
+                rest = &rest[start + 2 + end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Expand `~` and resolve relative paths against the config file's directory
+fn expand_config_path(value: &str, base: Option<&Path>) -> PathBuf {
+    let path = expand_home(value);
+    match base {
+        Some(base) if path.is_relative() => base.join(path),
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_npmrc_last_assignment_wins() {
+        let content = "; comment\ncache=/first\nregistry = https://registry.npmjs.org/\n# cache=/commented\ncache = \"/second\"\n";
+        assert_eq!(parse_npmrc(content, "cache").as_deref(), Some("/second"));
+        assert_eq!(parse_npmrc(content, "store-dir"), None);
+    }
+
+    #[test]
+    fn test_parse_npmrc_expands_env_refs() {
+        let home = std::env::var("HOME").unwrap_or_default();
+        let content = "store-dir=${HOME}/pnpm-store\n";
+        assert_eq!(
+            parse_npmrc(content, "store-dir"),
+            Some(format!("{}/pnpm-store", home))
+        );
+    }
+
+    #[test]
+    fn test_parse_yarnrc_yml_reads_top_level_keys() {
+        let content = "nodeLinker: node-modules\ncacheFolder: \"./cache\" # local\npackageExtensions:\n  globalFolder: /nested\n";
+        assert_eq!(
+            parse_yarnrc_yml(content, "cacheFolder").as_deref(),
+            Some("./cache")
+        );
+        assert_eq!(parse_yarnrc_yml(content, "globalFolder"), None);
+    }
+
+    #[test]
+    fn test_project_root_is_the_nearest_package_json() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().join("app");
+        let nested = root.join("src/components");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join("package.json"), "{}").unwrap();
+
+        assert_eq!(project_root_from(&nested), root);
+        assert_eq!(project_root_from(&root), root);
+        // Outside any project the directory itself is used
+        assert_eq!(project_root_from(temp_dir.path()), temp_dir.path());
+    }
+
+    #[test]
+    fn test_expand_config_path_resolves_relative_to_rc_file() {
+        let base = Path::new("/work/project");
+        assert_eq!(
+            expand_config_path("./cache", Some(base)),
+            PathBuf::from("/work/project/./cache")
+        );
+        assert_eq!(
+            expand_config_path("/abs/cache", Some(base)),
+            PathBuf::from("/abs/cache")
+        );
+    }
+
+    #[test]
+    fn test_config_source_display() {
+        assert_eq!(
+            ConfigSource::Env("npm_config_cache".to_string()).to_string(),
+            "env npm_config_cache"
+        );
+        assert_eq!(ConfigSource::Default.to_string(), "default");
+    }
+}
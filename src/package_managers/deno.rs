@@ -0,0 +1,141 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::cache_entry::CacheEntry;
+use crate::config::MergedConfig;
+
+use super::common::{env_value, existing_dir, make_resolved_entry, ConfigSource, ResolvedDir};
+use super::traits::CacheManager;
+
+/// Subdirectories of `DENO_DIR` that hold downloaded or generated data
+const DENO_DIR_CACHES: &[&str] = &["deps", "gen", "npm", "registries"];
+
+pub struct DenoManager {
+    pub(crate) config: MergedConfig,
+}
+
+impl DenoManager {
+    pub fn new(config: MergedConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// Resolve `DENO_DIR`: the env var, then the platform cache directory
+pub fn deno_dir_location() -> Option<(PathBuf, ConfigSource)> {
+    env_value(&["DENO_DIR"])
+        .map(|(name, dir)| (PathBuf::from(dir), ConfigSource::Env(name)))
+        .or_else(|| {
+            #[cfg(all(unix, not(target_os = "macos")))]
+            if let Some((name, cache)) = env_value(&["XDG_CACHE_HOME"]) {
+                return Some((
+                    PathBuf::from(cache).join("deno"),
+                    ConfigSource::Derived(format!("${}/deno", name)),
+                ));
+            }
+            default_deno_dir().map(|dir| (dir, ConfigSource::Default))
+        })
+}
+
+fn default_deno_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var_os("LOCALAPPDATA").map(|local| PathBuf::from(local).join("deno"))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        dirs::home_dir().map(|home| home.join("Library").join("Caches").join("deno"))
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        dirs::home_dir().map(|home| home.join(".cache").join("deno"))
+    }
+}
+
+/// `gen/<scheme>/<host>` directories, so compiled output can be cleared per remote host
+fn gen_host_dirs(gen: &Path) -> Vec<PathBuf> {
+    let mut hosts = Vec::new();
+    let Ok(schemes) = std::fs::read_dir(gen) else {
+        return hosts;
+    };
+    for scheme in schemes.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if !scheme.is_dir() {
+            continue;
+        }
+        if let Ok(entries) = std::fs::read_dir(&scheme) {
+            hosts.extend(
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_dir()),
+            );
+        }
+    }
+    hosts.sort();
+    hosts
+}
+
+impl CacheManager for DenoManager {
+    fn name(&self) -> &'static str {
+        "deno"
+    }
+
+    fn locations(&self) -> Vec<ResolvedDir> {
+        let mut locations = Vec::new();
+        if let Some((dir, source)) = deno_dir_location() {
+            for sub in DENO_DIR_CACHES {
+                locations.push(ResolvedDir::new(sub, dir.join(sub), source.clone()));
+            }
+        }
+        if let Ok(cwd) = std::env::current_dir() {
+            locations.push(ResolvedDir::new(
+                "project node_modules/.deno",
+                cwd.join("node_modules").join(".deno"),
+                ConfigSource::Default,
+            ));
+        }
+        locations
+    }
+
+    fn list(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        for location in self.locations() {
+            if !existing_dir(&location.path) {
+                continue;
+            }
+            if location.label == "gen" {
+                for host in gen_host_dirs(&location.path) {
+                    let host = ResolvedDir::new("gen", host, location.source.clone());
+                    entries.push(make_resolved_entry(&host, &self.config)?);
+                }
+            } else {
+                entries.push(make_resolved_entry(&location, &self.config)?);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_gen_host_dirs_lists_each_remote_host() {
+        let temp_dir = TempDir::new().unwrap();
+        let gen = temp_dir.path().join("gen");
+        std::fs::create_dir_all(gen.join("https").join("deno.land")).unwrap();
+        std::fs::create_dir_all(gen.join("https").join("esm.sh")).unwrap();
+        std::fs::create_dir_all(gen.join("file").join("home")).unwrap();
+        std::fs::write(gen.join("https").join("stray.js"), "").unwrap();
+
+        assert_eq!(
+            gen_host_dirs(&gen),
+            vec![
+                gen.join("file").join("home"),
+                gen.join("https").join("deno.land"),
+                gen.join("https").join("esm.sh"),
+            ]
+        );
+    }
+}
@@ -1,13 +1,30 @@
+pub mod bun;
+pub mod cacache;
 pub mod common;
+pub mod deno;
 pub mod npm;
 pub mod pnpm;
+pub mod pnpm_store;
 pub mod traits;
 pub mod yarn;
 
-use crate::cache_entry::CacheEntry;
+use crate::cache_entry::{CacheEntry, CacheKind};
 use crate::config::MergedConfig;
+use crate::regen::{RegenCost, RegenSource};
+use crate::rules::RuleSet;
 use anyhow::Result;
-use traits::CacheManager;
+use serde::{Deserialize, Serialize};
+use traits::{CacheManager, Capabilities, CleanMode, CleanOutcome, VerifyReport};
+
+/// A resolved package manager cache location, as shown by `--js-pm --list` and `doctor`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PmLocation {
+    pub manager: String,
+    pub label: String,
+    pub path: String,
+    pub source: String,
+    pub exists: bool,
+}
 
 pub struct PackageManagers {
     config: MergedConfig,
@@ -23,15 +40,77 @@ impl PackageManagers {
             Box::new(npm::NpmManager::new(self.config.clone())),
             Box::new(pnpm::PnpmManager::new(self.config.clone())),
             Box::new(yarn::YarnManager::new(self.config.clone())),
+            Box::new(bun::BunManager::new(self.config.clone())),
+            Box::new(deno::DenoManager::new(self.config.clone())),
         ]
     }
 
+    /// Resolved cache locations for every manager, whether or not they exist
+    pub fn locations(&self) -> Vec<PmLocation> {
+        self.managers()
+            .iter()
+            .flat_map(|m| {
+                m.locations().into_iter().map(|location| PmLocation {
+                    manager: m.name().to_string(),
+                    label: location.label.to_string(),
+                    exists: common::existing_dir(&location.path),
+                    path: location.path.to_string_lossy().to_string(),
+                    source: location.source.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Clean `entry` through the manager whose locations contain it.
+    /// Returns `None` when no manager owns the path.
+    pub fn clean(&self, entry: &CacheEntry, mode: CleanMode) -> Option<Result<CleanOutcome>> {
+        let owner = self.managers().into_iter().find(|m| {
+            m.locations()
+                .iter()
+                .any(|location| entry.path.starts_with(&location.path))
+        })?;
+        Some(
+            owner
+                .clean(std::slice::from_ref(entry), mode, self.config.stale_days)
+                .map(|mut outcomes| outcomes.remove(0)),
+        )
+    }
+
+    /// Supported modes and native command for every manager
+    pub fn capabilities(&self) -> Vec<Capabilities> {
+        self.managers().iter().map(|m| m.capabilities()).collect()
+    }
+
+    /// Run each manager's cache check
+    pub fn verify_all(&self) -> Vec<VerifyReport> {
+        self.managers()
+            .iter()
+            .map(|m| {
+                m.verify().unwrap_or_else(|e| VerifyReport {
+                    manager: m.name().to_string(),
+                    ok: false,
+                    method: traits::CleanMethod::Filesystem,
+                    details: vec![e.to_string()],
+                })
+            })
+            .collect()
+    }
+
     pub fn list_all(&self) -> Result<Vec<CacheEntry>> {
         let managers: Vec<Box<dyn CacheManager>> = self.managers();
         let mut all = Vec::new();
         for m in managers {
-            let mut entries = m.list()?;
-            all.append(&mut entries);
+            let entries = m.list()?;
+            all.extend(entries.into_iter().map(|e| {
+                // Only what a prune removes has to be downloaded again
+                let bytes = e.reclaimable_bytes.unwrap_or(e.size_bytes);
+                e.with_manager(m.name())
+                    .with_regen_cost(RegenCost::estimate(
+                        CacheKind::JavaScript,
+                        RegenSource::Network,
+                        bytes,
+                    ))
+            }));
         }
         Ok(all)
     }
@@ -47,15 +126,44 @@ pub fn add_js_pm_entries(entries: &mut Vec<CacheEntry>, config: &MergedConfig) -
     }
     let pm = PackageManagers::new(config.clone());
     let mut pm_entries = pm.list_all()?;
+    RuleSet::new(&config.rules).apply(&mut pm_entries);
     entries.append(&mut pm_entries);
     Ok(())
 }
 
+/// Handle `--js-pm --verify`: run each manager's cache check and report
+pub fn handle_verify(config: &MergedConfig) -> Result<()> {
+    let reports = PackageManagers::new(config.clone()).verify_all();
+
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        println!("🩺 Package Manager Cache Check");
+        for report in &reports {
+            let status = if report.ok { "✅" } else { "❌" };
+            let method = match &report.method {
+                traits::CleanMethod::Native(command) => command.clone(),
+                traits::CleanMethod::Filesystem => "filesystem".to_string(),
+            };
+            println!("  {}: {} (via {})", report.manager, status, method);
+            for detail in &report.details {
+                println!("    {}", detail);
+            }
+        }
+    }
+
+    if reports.iter().any(|r| !r.ok) {
+        std::process::exit(crate::ci::exit_codes::PARTIAL_SUCCESS);
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn add_js_pm_entries_is_noop_when_flag_is_false() {
         let mut entries = Vec::new();
         let mut cfg = MergedConfig::default();
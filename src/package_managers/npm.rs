@@ -1,11 +1,16 @@
 use anyhow::Result;
-use std::path::{Path, PathBuf};
+use chrono::{Duration, Utc};
+use std::path::PathBuf;
 
-use crate::cache_entry::CacheEntry;
+use crate::cache_entry::{CacheEntry, CacheKind, PlannedAction};
 use crate::config::MergedConfig;
+use crate::util::get_size;
 
-use super::common::{existing_dir, make_entry};
-use super::traits::CacheManager;
+use super::cacache;
+use super::common::{
+    existing_dir, make_resolved_entry, npm_env_value, npmrc_value, ConfigSource, ResolvedDir,
+};
+use super::traits::{CacheManager, CleanMode};
 
 pub struct NpmManager {
     pub(crate) config: MergedConfig,
@@ -16,10 +21,18 @@ impl NpmManager {
         Self { config }
     }
 
-    fn cache_dir() -> Option<PathBuf> {
+    /// Resolve the npm cache: `npm_config_cache` > project `.npmrc` > user `.npmrc` > default
+    pub fn cache_location() -> Option<ResolvedDir> {
+        if let Some((path, source)) = npm_env_value("cache").or_else(|| npmrc_value("cache")) {
+            return Some(ResolvedDir::new("cache", path, source));
+        }
+        Self::default_cache_dir().map(|path| ResolvedDir::new("cache", path, ConfigSource::Default))
+    }
+
+    fn default_cache_dir() -> Option<PathBuf> {
         #[cfg(target_os = "windows")]
         {
-            std::env::var_os("LOCALAPPDATA").map(|local| Path::new(&local).join("npm-cache"))
+            std::env::var_os("LOCALAPPDATA").map(|local| PathBuf::from(local).join("npm-cache"))
         }
         #[cfg(target_os = "macos")]
         {
@@ -30,6 +43,21 @@ impl NpmManager {
             dirs::home_dir().map(|home| home.join(".npm"))
         }
     }
+
+    /// List `_cacache` as a prunable entry carrying the bytes an age-based trim would free
+    fn cacache_entry(&self, dir: PathBuf, source: &ConfigSource) -> Result<CacheEntry> {
+        let cutoff = Utc::now() - Duration::days(self.config.stale_days as i64);
+        let plan = cacache::plan_prune(&dir, cutoff)?;
+        let size = get_size(&dir)?;
+        let last_used = plan.last_used.unwrap_or(cutoff);
+        let stale = plan.reclaimable_bytes > 0;
+        Ok(
+            CacheEntry::new(dir, CacheKind::JavaScript, size, last_used, stale)
+                .with_planned_action(PlannedAction::Prune)
+                .with_reclaimable_bytes(plan.reclaimable_bytes)
+                .with_config_source(source.to_string()),
+        )
+    }
 }
 
 impl CacheManager for NpmManager {
@@ -37,21 +65,39 @@ impl CacheManager for NpmManager {
         "npm"
     }
 
+    fn locations(&self) -> Vec<ResolvedDir> {
+        Self::cache_location().into_iter().collect()
+    }
+
+    fn binary(&self) -> Option<&'static str> {
+        Some("npm")
+    }
+
+    /// `npm cache clean --force` wipes `_cacache`; age-based pruning stays ours
+    fn native_clean_args(&self, entry: &CacheEntry, mode: CleanMode) -> Option<Vec<&'static str>> {
+        let location = Self::cache_location()?;
+        let owned = entry.path == location.path || entry.path == location.path.join("_cacache");
+        (mode == CleanMode::Delete && owned).then(|| vec!["cache", "clean", "--force"])
+    }
+
+    fn native_verify_args(&self) -> Option<Vec<&'static str>> {
+        Some(vec!["cache", "verify"])
+    }
+
+    fn supports(&self, _mode: CleanMode) -> bool {
+        true
+    }
+
     fn list(&self) -> Result<Vec<CacheEntry>> {
         let mut entries = Vec::new();
-        if let Some(dir) = Self::cache_dir() {
-            if existing_dir(&dir) {
-                entries.push(make_entry(dir, &self.config)?);
+        for location in self.locations() {
+            let cacache_dir = location.path.join("_cacache");
+            if cacache::is_cacache_dir(&cacache_dir) {
+                entries.push(self.cacache_entry(cacache_dir, &location.source)?);
+            } else if existing_dir(&location.path) {
+                entries.push(make_resolved_entry(&location, &self.config)?);
             }
         }
         Ok(entries)
     }
-
-    fn exclude_patterns(&self) -> Vec<String> {
-        let mut patterns = Vec::new();
-        if let Some(dir) = Self::cache_dir() {
-            patterns.push(dir.to_string_lossy().to_string());
-        }
-        patterns
-    }
 }
@@ -1,11 +1,19 @@
 use anyhow::Result;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-use crate::cache_entry::CacheEntry;
+use crate::cache_entry::{CacheEntry, CacheKind, PlannedAction};
 use crate::config::MergedConfig;
+use crate::util::{get_most_recent_mtime, get_size};
 
-use super::common::{existing_dir, make_entry};
-use super::traits::CacheManager;
+use super::common::{
+    env_value, existing_dir, make_resolved_entry, npm_env_value, npmrc_value, ConfigSource,
+    ResolvedDir,
+};
+use super::pnpm_store;
+use super::traits::{CacheManager, CleanMode};
+
+/// Layout version pnpm appends to the configured store directory
+const STORE_VERSION: &str = "v3";
 
 pub struct PnpmManager {
     pub(crate) config: MergedConfig,
@@ -15,34 +23,88 @@ impl PnpmManager {
     pub fn new(config: MergedConfig) -> Self {
         Self { config }
     }
+
+    /// List the store as a prunable entry: only packages no project links to are reclaimable
+    fn store_entry(&self, location: &ResolvedDir) -> Result<CacheEntry> {
+        let plan = pnpm_store::plan_prune(&location.path)?;
+        let size = get_size(&location.path)?;
+        let last_used = get_most_recent_mtime(&location.path)?;
+        let stale = plan.reclaimable_bytes > 0;
+        Ok(CacheEntry::new(
+            location.path.clone(),
+            CacheKind::JavaScript,
+            size,
+            last_used,
+            stale,
+        )
+        .with_planned_action(PlannedAction::Prune)
+        .with_reclaimable_bytes(plan.reclaimable_bytes)
+        .with_shared_bytes(plan.shared_bytes)
+        .with_config_source(location.source.to_string()))
+    }
+}
+
+/// Resolve the content-addressable store:
+/// `store-dir` (env, then `.npmrc`) > `$PNPM_HOME/store` > `$XDG_DATA_HOME/pnpm/store` > default
+pub fn store_location() -> Option<ResolvedDir> {
+    let (root, source) = npm_env_value("store-dir")
+        .or_else(|| npmrc_value("store-dir"))
+        .or_else(|| {
+            env_value(&["PNPM_HOME"]).map(|(name, home)| {
+                (
+                    PathBuf::from(home).join("store"),
+                    ConfigSource::Derived(format!("${}/store", name)),
+                )
+            })
+        })
+        .or_else(|| {
+            env_value(&["XDG_DATA_HOME"]).map(|(name, data)| {
+                (
+                    PathBuf::from(data).join("pnpm").join("store"),
+                    ConfigSource::Derived(format!("${}/pnpm/store", name)),
+                )
+            })
+        })
+        .or_else(|| default_store_root().map(|root| (root, ConfigSource::Default)))?;
+    Some(ResolvedDir::new("store", root.join(STORE_VERSION), source))
 }
 
-fn store_dir() -> Option<PathBuf> {
+/// Resolve the metadata cache: `cache-dir` (env, then `.npmrc`) > `$XDG_CACHE_HOME/pnpm` > default
+pub fn meta_cache_location() -> Option<ResolvedDir> {
+    let (path, source) = npm_env_value("cache-dir")
+        .or_else(|| npmrc_value("cache-dir"))
+        .or_else(|| {
+            env_value(&["XDG_CACHE_HOME"]).map(|(name, cache)| {
+                (
+                    PathBuf::from(cache).join("pnpm"),
+                    ConfigSource::Derived(format!("${}/pnpm", name)),
+                )
+            })
+        })
+        .or_else(|| default_meta_cache_dir().map(|dir| (dir, ConfigSource::Default)))?;
+    Some(ResolvedDir::new("metadata cache", path, source))
+}
+
+fn default_store_root() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
         std::env::var_os("LOCALAPPDATA")
-            .map(|local| Path::new(&local).join("pnpm").join("store").join("v3"))
+            .map(|local| PathBuf::from(local).join("pnpm").join("store"))
     }
     #[cfg(target_os = "macos")]
     {
-        dirs::home_dir().map(|home| home.join("Library").join("pnpm").join("store").join("v3"))
+        dirs::home_dir().map(|home| home.join("Library").join("pnpm").join("store"))
     }
     #[cfg(all(unix, not(target_os = "macos")))]
     {
-        dirs::home_dir().map(|home| {
-            home.join(".local")
-                .join("share")
-                .join("pnpm")
-                .join("store")
-                .join("v3")
-        })
+        dirs::home_dir().map(|home| home.join(".local").join("share").join("pnpm").join("store"))
     }
 }
 
-fn meta_cache_dir() -> Option<PathBuf> {
+fn default_meta_cache_dir() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
-        std::env::var_os("LOCALAPPDATA").map(|local| Path::new(&local).join("pnpm-cache"))
+        std::env::var_os("LOCALAPPDATA").map(|local| PathBuf::from(local).join("pnpm-cache"))
     }
     #[cfg(target_os = "macos")]
     {
@@ -59,29 +121,36 @@ impl CacheManager for PnpmManager {
         "pnpm"
     }
 
+    fn locations(&self) -> Vec<ResolvedDir> {
+        store_location()
+            .into_iter()
+            .chain(meta_cache_location())
+            .collect()
+    }
+
+    fn binary(&self) -> Option<&'static str> {
+        Some("pnpm")
+    }
+
+    /// `pnpm store prune` knows every project's references, so it beats link counting
+    fn native_clean_args(&self, entry: &CacheEntry, mode: CleanMode) -> Option<Vec<&'static str>> {
+        let store = store_location()?;
+        (mode == CleanMode::Prune && entry.path == store.path).then(|| vec!["store", "prune"])
+    }
+
+    fn supports(&self, _mode: CleanMode) -> bool {
+        true
+    }
+
     fn list(&self) -> Result<Vec<CacheEntry>> {
         let mut entries = Vec::new();
-        if let Some(dir) = store_dir() {
-            if existing_dir(&dir) {
-                entries.push(make_entry(dir, &self.config)?);
-            }
-        }
-        if let Some(dir) = meta_cache_dir() {
-            if existing_dir(&dir) {
-                entries.push(make_entry(dir, &self.config)?);
+        for location in self.locations() {
+            if pnpm_store::is_pnpm_store(&location.path) {
+                entries.push(self.store_entry(&location)?);
+            } else if existing_dir(&location.path) {
+                entries.push(make_resolved_entry(&location, &self.config)?);
             }
         }
         Ok(entries)
     }
-
-    fn exclude_patterns(&self) -> Vec<String> {
-        let mut patterns = Vec::new();
-        if let Some(d) = store_dir() {
-            patterns.push(d.to_string_lossy().to_string());
-        }
-        if let Some(d) = meta_cache_dir() {
-            patterns.push(d.to_string_lossy().to_string());
-        }
-        patterns
-    }
 }
@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::cacache::decode_base64;
+
+/*
+ * pnpm's store (`store/v3`) is content-addressable:
+ *   files/<aa>/<rest>[-exec]    file contents, hard-linked into each project's node_modules/.pnpm
+ *   files/<aa>/<rest>-index.json  per-package index mapping file names to content integrities
+ * A content file nobody links to has a link count of 1 (the store's own link).
+ * A package is only removed when none of its files are linked from a project, so no
+ * project is ever left with a half-present package.
+ */
+const FILES_DIR: &str = "files";
+const INDEX_SUFFIX: &str = "-index.json";
+const EXEC_SUFFIX: &str = "-exec";
+
+/// Package index file (only the fields needed for pruning)
+#[derive(Debug, Clone, Deserialize)]
+struct PackageIndex {
+    #[serde(default)]
+    files: HashMap<String, IndexedFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IndexedFile {
+    integrity: String,
+    #[serde(default)]
+    mode: u32,
+}
+
+/// A content file and its hard-link count
+#[derive(Debug, Clone, Copy)]
+struct ContentFile {
+    size: u64,
+    links: u64,
+}
+
+/// What pruning a pnpm store would remove
+#[derive(Debug, Clone, Default)]
+pub struct StorePrunePlan {
+    /// Packages with no file linked from any project
+    pub unreferenced_packages: usize,
+    /// Packages with at least one file linked from a project
+    pub referenced_packages: usize,
+    /// Index and content files that are safe to remove
+    pub removable: Vec<PathBuf>,
+    /// Bytes freed by removing `removable`
+    pub reclaimable_bytes: u64,
+    /// Bytes of content still hard-linked into projects
+    pub shared_bytes: u64,
+}
+
+/// Check whether a directory looks like a pnpm content-addressable store
+pub fn is_pnpm_store(path: &Path) -> bool {
+    path.join(FILES_DIR).is_dir()
+}
+
+/// Analyze a pnpm store, finding packages no project links to
+pub fn plan_prune(root: &Path) -> Result<StorePrunePlan> {
+    let mut plan = StorePrunePlan::default();
+    let mut content: HashMap<PathBuf, ContentFile> = HashMap::new();
+    let mut indexes: Vec<(PathBuf, u64)> = Vec::new();
+
+    let files_dir = root.join(FILES_DIR);
+    for entry in WalkDir::new(&files_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let metadata = entry
+            .metadata()
+            .with_context(|| format!("Failed to stat {}", entry.path().display()))?;
+        let path = entry.path().to_path_buf();
+        if is_index_file(&path) {
+            indexes.push((path, metadata.len()));
+        } else {
+            content.insert(
+                path,
+                ContentFile {
+                    size: metadata.len(),
+                    links: link_count(&metadata),
+                },
+            );
+        }
+    }
+
+    // Files belonging to a package that is still linked somewhere are never removed
+    let mut kept: HashSet<PathBuf> = HashSet::new();
+    for (index_path, index_size) in &indexes {
+        let raw = fs::read_to_string(index_path)
+            .with_context(|| format!("Failed to read package index {}", index_path.display()))?;
+        let Ok(index) = serde_json::from_str::<PackageIndex>(&raw) else {
+            continue;
+        };
+        let files: Vec<PathBuf> = index
+            .files
+            .values()
+            .filter_map(|file| content_path(root, file))
+            .collect();
+        let referenced = files
+            .iter()
+            .any(|file| content.get(file).is_some_and(|c| c.links > 1));
+        if referenced {
+            plan.referenced_packages += 1;
+            kept.extend(files);
+        } else {
+            plan.unreferenced_packages += 1;
+            plan.reclaimable_bytes += index_size;
+            plan.removable.push(index_path.clone());
+        }
+    }
+
+    for (path, file) in &content {
+        if file.links > 1 {
+            plan.shared_bytes += file.size;
+            continue;
+        }
+        // Unlinked files of unreferenced packages, plus content no index mentions
+        if !kept.contains(path) {
+            plan.reclaimable_bytes += file.size;
+            plan.removable.push(path.clone());
+        }
+    }
+
+    plan.removable.sort();
+    Ok(plan)
+}
+
+/// Remove unreferenced packages from a pnpm store. Returns the number of bytes freed.
+pub fn prune(root: &Path) -> Result<u64> {
+    let plan = plan_prune(root)?;
+    let mut freed = 0u64;
+
+    for path in &plan.removable {
+        let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        fs::remove_file(path)
+            .with_context(|| format!("Failed to remove store file {}", path.display()))?;
+        freed += size;
+    }
+
+    Ok(freed)
+}
+
+fn is_index_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(INDEX_SUFFIX))
+}
+
+/// Map an indexed file to its content path (`files/<aa>/<rest>`, `-exec` for executables)
+fn content_path(root: &Path, file: &IndexedFile) -> Option<PathBuf> {
+    let (_, digest) = file.integrity.split_once('-')?;
+    let hex: String = decode_base64(digest)?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if hex.len() < 3 {
+        return None;
+    }
+    let mut name = hex[2..].to_string();
+    if file.mode & 0o111 != 0 {
+        name.push_str(EXEC_SUFFIX);
+    }
+    Some(root.join(FILES_DIR).join(&hex[0..2]).join(name))
+}
+
+#[cfg(unix)]
+fn link_count(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink()
+}
+
+/// Link counts are not exposed on this platform, so every file is treated as shared
+#[cfg(not(unix))]
+fn link_count(_metadata: &fs::Metadata) -> u64 {
+    u64::MAX
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const LINKED_INTEGRITY: &str = "sha512-AAECAwQFBgc=";
+    const UNLINKED_INTEGRITY: &str = "sha512-CAkKCwwNDg8=";
+    const ORPHAN_INTEGRITY: &str = "sha512-EBESExQVFhc=";
+
+    fn write_content(root: &Path, integrity: &str) -> PathBuf {
+        let file = IndexedFile {
+            integrity: integrity.to_string(),
+            mode: 0o644,
+        };
+        let path = content_path(root, &file).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "hello").unwrap();
+        path
+    }
+
+    fn write_index(root: &Path, name: &str, integrity: &str) -> PathBuf {
+        let path = root
+            .join(FILES_DIR)
+            .join("ff")
+            .join(format!("{}{}", name, INDEX_SUFFIX));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            format!(
+                r#"{{"name":"{}","version":"1.0.0","files":{{"index.js":{{"checkedAt":0,"integrity":"{}","mode":420,"size":5}}}}}}"#,
+                name, integrity
+            ),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_content_path_marks_executables() {
+        let root = Path::new("/store");
+        let file = IndexedFile {
+            integrity: LINKED_INTEGRITY.to_string(),
+            mode: 0o755,
+        };
+        assert_eq!(
+            content_path(root, &file).unwrap(),
+            root.join(FILES_DIR).join("00").join("01020304050607-exec")
+        );
+    }
+
+    #[test]
+    fn test_prune_keeps_linked_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("v3");
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+
+        let linked = write_content(&root, LINKED_INTEGRITY);
+        fs::hard_link(&linked, project.join("index.js")).unwrap();
+        let linked_index = write_index(&root, "linked", LINKED_INTEGRITY);
+
+        let unlinked = write_content(&root, UNLINKED_INTEGRITY);
+        let unlinked_index = write_index(&root, "unlinked", UNLINKED_INTEGRITY);
+        let orphan = write_content(&root, ORPHAN_INTEGRITY);
+
+        let plan = plan_prune(&root).unwrap();
+        assert_eq!(plan.referenced_packages, 1);
+        assert_eq!(plan.unreferenced_packages, 1);
+        assert_eq!(plan.shared_bytes, 5);
+        let mut expected = vec![unlinked.clone(), unlinked_index.clone(), orphan.clone()];
+        expected.sort();
+        assert_eq!(plan.removable, expected);
+
+        let freed = prune(&root).unwrap();
+        assert_eq!(freed, plan.reclaimable_bytes);
+        assert!(linked.exists());
+        assert!(linked_index.exists());
+        assert!(!unlinked.exists());
+        assert!(!unlinked_index.exists());
+        assert!(!orphan.exists());
+    }
+}
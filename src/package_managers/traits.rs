@@ -1,16 +1,320 @@
 use crate::cache_entry::CacheEntry;
-use anyhow::Result;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::common::{self, ResolvedDir};
+use crate::util::{get_size, path_exists};
+
+/// How a cache entry should be cleaned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CleanMode {
+    /// Trim unused content, keeping what projects still need
+    #[serde(rename = "prune")]
+    Prune,
+    /// Remove the whole cache
+    #[serde(rename = "delete")]
+    Delete,
+}
+
+/// What a package manager supports, so the UI can offer the right modes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub manager: String,
+    pub prune: bool,
+    pub delete: bool,
+    pub verify: bool,
+    /// The tool's own binary, when it is installed and cleaning is delegated to it
+    pub native_command: Option<String>,
+}
+
+/// How an entry was actually cleaned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CleanMethod {
+    /// Delegated to the tool, e.g. `npm cache clean --force`
+    #[serde(rename = "native")]
+    Native(String),
+    #[serde(rename = "filesystem")]
+    Filesystem,
+}
+
+/// Result of cleaning one entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanOutcome {
+    pub path: PathBuf,
+    pub freed: u64,
+    pub method: CleanMethod,
+}
+
+/// Result of checking a manager's caches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub manager: String,
+    pub ok: bool,
+    pub method: CleanMethod,
+    pub details: Vec<String>,
+}
 
 /// Trait implemented by each package manager cache handler
 pub trait CacheManager {
-    /// A short static name (e.g., "npm", "pnpm", "yarn")
-    #[allow(dead_code)]
+    /// A short static name (e.g., "npm", "pnpm", "yarn", "bun", "deno")
     fn name(&self) -> &'static str;
 
+    /// Cache locations after applying the tool's own configuration, with the source that won
+    fn locations(&self) -> Vec<ResolvedDir>;
+
     /// List cache entries for this package manager
     fn list(&self) -> Result<Vec<CacheEntry>>;
 
     /// Paths/patterns that should be excluded from backups
     #[allow(dead_code)]
-    fn exclude_patterns(&self) -> Vec<String>;
+    fn exclude_patterns(&self) -> Vec<String> {
+        self.locations()
+            .iter()
+            .map(|location| location.path.to_string_lossy().to_string())
+            .collect()
+    }
+
+    /// The tool's executable, used for native-command delegation
+    fn binary(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Arguments for the tool's own clean command for `entry`, if it has one for `mode`
+    fn native_clean_args(
+        &self,
+        _entry: &CacheEntry,
+        _mode: CleanMode,
+    ) -> Option<Vec<&'static str>> {
+        None
+    }
+
+    /// Arguments for the tool's own integrity check, if it has one
+    fn native_verify_args(&self) -> Option<Vec<&'static str>> {
+        None
+    }
+
+    /// Whether `mode` is supported at all (natively or on the filesystem)
+    fn supports(&self, mode: CleanMode) -> bool {
+        mode == CleanMode::Delete
+    }
+
+    /// Describe the modes this manager supports
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            manager: self.name().to_string(),
+            prune: self.supports(CleanMode::Prune),
+            delete: self.supports(CleanMode::Delete),
+            verify: true,
+            native_command: installed_binary(self.binary()),
+        }
+    }
+
+    /// Clean `entries`, delegating to the tool when it is installed and falling
+    /// back to the filesystem when it is not or when the command fails
+    fn clean(
+        &self,
+        entries: &[CacheEntry],
+        mode: CleanMode,
+        stale_days: u32,
+    ) -> Result<Vec<CleanOutcome>> {
+        let mut outcomes = Vec::new();
+        for entry in entries {
+            let before = get_size(&entry.path).unwrap_or(entry.size_bytes);
+            let native = installed_binary(self.binary())
+                .zip(self.native_clean_args(entry, mode))
+                .and_then(|(binary, args)| run_native(&binary, &args).ok());
+
+            let method = match native {
+                Some(command) => CleanMethod::Native(command),
+                None => {
+                    match mode {
+                        CleanMode::Delete => common::delete_dir(&entry.path)?,
+                        CleanMode::Prune => {
+                            common::prune_dir(&entry.path, stale_days)?;
+                        }
+                    }
+                    CleanMethod::Filesystem
+                }
+            };
+
+            let after = if path_exists(&entry.path) {
+                get_size(&entry.path).unwrap_or(0)
+            } else {
+                0
+            };
+            outcomes.push(CleanOutcome {
+                path: entry.path.clone(),
+                freed: before.saturating_sub(after),
+                method,
+            });
+        }
+        Ok(outcomes)
+    }
+
+    /// Check the caches are usable: the tool's own check when installed,
+    /// otherwise that every existing location is a readable directory
+    fn verify(&self) -> Result<VerifyReport> {
+        if let Some((binary, args)) = installed_binary(self.binary()).zip(self.native_verify_args())
+        {
+            let command = format!("{} {}", self.binary().unwrap_or_default(), args.join(" "));
+            let output = Command::new(&binary)
+                .args(&args)
+                .output()
+                .with_context(|| format!("Failed to run {}", command))?;
+            let details = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .chain(String::from_utf8_lossy(&output.stderr).lines())
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+            return Ok(VerifyReport {
+                manager: self.name().to_string(),
+                ok: output.status.success(),
+                method: CleanMethod::Native(command),
+                details,
+            });
+        }
+
+        let mut ok = true;
+        let mut details = Vec::new();
+        for location in self.locations() {
+            if !path_exists(&location.path) {
+                continue;
+            }
+            match std::fs::read_dir(&location.path) {
+                Ok(_) => details.push(format!(
+                    "{}: {} readable",
+                    location.label,
+                    location.path.display()
+                )),
+                Err(e) => {
+                    ok = false;
+                    details.push(format!(
+                        "{}: {} unreadable: {}",
+                        location.label,
+                        location.path.display(),
+                        e
+                    ));
+                }
+            }
+        }
+        Ok(VerifyReport {
+            manager: self.name().to_string(),
+            ok,
+            method: CleanMethod::Filesystem,
+            details,
+        })
+    }
+}
+
+/// Full path of `binary` when it is on `PATH`
+fn installed_binary(binary: Option<&str>) -> Option<String> {
+    which::which(binary?)
+        .ok()
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Run a native command, returning its display form on success
+fn run_native(binary: &str, args: &[&str]) -> Result<String> {
+    let name = std::path::Path::new(binary)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| binary.to_string());
+    let command = format!("{} {}", name, args.join(" "));
+    // Capture output so the tool's chatter never mixes with ours (or with --json)
+    let output = Command::new(binary)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}", command))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} exited with {}: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache_entry::{CacheKind, PlannedAction};
+    use chrono::Utc;
+    use common::ConfigSource;
+    use tempfile::TempDir;
+
+    /// A manager whose tool is never installed
+    struct FakeManager {
+        dir: PathBuf,
+    }
+
+    impl CacheManager for FakeManager {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn locations(&self) -> Vec<ResolvedDir> {
+            vec![ResolvedDir::new(
+                "cache",
+                self.dir.clone(),
+                ConfigSource::Default,
+            )]
+        }
+
+        fn list(&self) -> Result<Vec<CacheEntry>> {
+            Ok(Vec::new())
+        }
+
+        fn binary(&self) -> Option<&'static str> {
+            Some("cachekill-test-missing-tool")
+        }
+
+        fn native_clean_args(
+            &self,
+            _entry: &CacheEntry,
+            _mode: CleanMode,
+        ) -> Option<Vec<&'static str>> {
+            Some(vec!["cache", "clean"])
+        }
+    }
+
+    #[test]
+    fn test_clean_falls_back_to_filesystem_when_tool_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("cache");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("blob"), "hello").unwrap();
+
+        let manager = FakeManager { dir: dir.clone() };
+        let entry = CacheEntry::new(dir.clone(), CacheKind::JavaScript, 5, Utc::now(), true)
+            .with_planned_action(PlannedAction::Delete);
+
+        let outcomes = manager.clean(&[entry], CleanMode::Delete, 14).unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].method, CleanMethod::Filesystem);
+        assert_eq!(outcomes[0].freed, 5);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_default_capabilities_and_verify() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = FakeManager {
+            dir: temp_dir.path().to_path_buf(),
+        };
+
+        let caps = manager.capabilities();
+        assert!(caps.delete);
+        assert!(!caps.prune);
+        assert!(caps.native_command.is_none());
+
+        let report = manager.verify().unwrap();
+        assert!(report.ok);
+        assert_eq!(report.method, CleanMethod::Filesystem);
+        assert_eq!(report.details.len(), 1);
+    }
 }
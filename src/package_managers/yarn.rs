@@ -1,11 +1,13 @@
 use anyhow::Result;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 use crate::cache_entry::CacheEntry;
 use crate::config::MergedConfig;
 
-use super::common::{existing_dir, make_entry};
-use super::traits::CacheManager;
+use super::common::{
+    env_value, existing_dir, make_resolved_entry, yarnrc_value, ConfigSource, ResolvedDir,
+};
+use super::traits::{CacheManager, CleanMode};
 
 pub struct YarnManager {
     pub(crate) config: MergedConfig,
@@ -17,10 +19,52 @@ impl YarnManager {
     }
 }
 
-fn global_cache_dir() -> Option<PathBuf> {
+/// Resolve the global cache: `YARN_CACHE_FOLDER` > `YARN_GLOBAL_FOLDER/cache` >
+/// `.yarnrc.yml` `globalFolder/cache` > `$XDG_CACHE_HOME/yarn` > default
+pub fn global_cache_location() -> Option<ResolvedDir> {
+    let (path, source) = env_value(&["YARN_CACHE_FOLDER"])
+        .map(|(name, folder)| (PathBuf::from(folder), ConfigSource::Env(name)))
+        .or_else(|| {
+            env_value(&["YARN_GLOBAL_FOLDER"]).map(|(name, folder)| {
+                (
+                    PathBuf::from(folder).join("cache"),
+                    ConfigSource::Derived(format!("${}/cache", name)),
+                )
+            })
+        })
+        .or_else(|| {
+            yarnrc_value("globalFolder").map(|(folder, source)| (folder.join("cache"), source))
+        })
+        .or_else(|| {
+            env_value(&["XDG_CACHE_HOME"]).map(|(name, cache)| {
+                (
+                    PathBuf::from(cache).join("yarn"),
+                    ConfigSource::Derived(format!("${}/yarn", name)),
+                )
+            })
+        })
+        .or_else(|| default_global_cache_dir().map(|dir| (dir, ConfigSource::Default)))?;
+    Some(ResolvedDir::new("global cache", path, source))
+}
+
+/// Resolve the project cache: `.yarnrc.yml` `cacheFolder` > `.yarn/cache`
+pub fn project_cache_location() -> Option<ResolvedDir> {
+    if let Some((path, source)) = yarnrc_value("cacheFolder") {
+        return Some(ResolvedDir::new("project cache", path, source));
+    }
+    let cwd = std::env::current_dir().ok()?;
+    Some(ResolvedDir::new(
+        "project cache",
+        cwd.join(".yarn").join("cache"),
+        ConfigSource::Default,
+    ))
+}
+
+fn default_global_cache_dir() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
-        std::env::var_os("LOCALAPPDATA").map(|local| Path::new(&local).join("Yarn").join("Cache"))
+        std::env::var_os("LOCALAPPDATA")
+            .map(|local| PathBuf::from(local).join("Yarn").join("Cache"))
     }
     #[cfg(target_os = "macos")]
     {
@@ -32,44 +76,43 @@ fn global_cache_dir() -> Option<PathBuf> {
     }
 }
 
-fn project_cache_dir() -> Option<PathBuf> {
-    if let Ok(cwd) = std::env::current_dir() {
-        let p = cwd.join(".yarn").join("cache");
-        if p.exists() && p.is_dir() {
-            return Some(p);
-        }
-    }
-    None
-}
-
 impl CacheManager for YarnManager {
     fn name(&self) -> &'static str {
         "yarn"
     }
 
-    fn list(&self) -> Result<Vec<CacheEntry>> {
-        let mut entries = Vec::new();
-        if let Some(dir) = global_cache_dir() {
-            if existing_dir(&dir) {
-                entries.push(make_entry(dir, &self.config)?);
-            }
+    fn locations(&self) -> Vec<ResolvedDir> {
+        global_cache_location()
+            .into_iter()
+            .chain(project_cache_location())
+            .collect()
+    }
+
+    fn binary(&self) -> Option<&'static str> {
+        Some("yarn")
+    }
+
+    /// Yarn Berry cleans the global mirror with `--mirror` and the project cache without it
+    fn native_clean_args(&self, entry: &CacheEntry, mode: CleanMode) -> Option<Vec<&'static str>> {
+        if mode != CleanMode::Delete {
+            return None;
         }
-        if let Some(dir) = project_cache_dir() {
-            if existing_dir(&dir) {
-                entries.push(make_entry(dir, &self.config)?);
-            }
+        if global_cache_location().is_some_and(|l| entry.path == l.path) {
+            return Some(vec!["cache", "clean", "--mirror"]);
         }
-        Ok(entries)
+        if project_cache_location().is_some_and(|l| entry.path == l.path) {
+            return Some(vec!["cache", "clean"]);
+        }
+        None
     }
 
-    fn exclude_patterns(&self) -> Vec<String> {
-        let mut patterns = Vec::new();
-        if let Some(d) = global_cache_dir() {
-            patterns.push(d.to_string_lossy().to_string());
-        }
-        if let Some(d) = project_cache_dir() {
-            patterns.push(d.to_string_lossy().to_string());
+    fn list(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        for location in self.locations() {
+            if existing_dir(&location.path) {
+                entries.push(make_resolved_entry(&location, &self.config)?);
+            }
         }
-        patterns
+        Ok(entries)
     }
 }
@@ -0,0 +1,183 @@
+use anyhow::Result;
+use inquire::error::InquireError;
+use inquire::list_option::ListOption;
+use inquire::{MultiSelect, Select};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache_entry::{CacheEntry, PlannedAction};
+use crate::util;
+
+/// Children listed when an entry is expanded
+const CHILDREN_SHOWN: usize = 10;
+
+/// Rows visible at once in the picker
+const PAGE_SIZE: usize = 15;
+
+/// One selectable row, showing size, kind, last use and staleness
+struct Row<'a> {
+    entry: &'a CacheEntry,
+}
+
+impl fmt::Display for Row<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entry = self.entry;
+        write!(
+            f,
+            "{:>10}  {:<8} {:<10} {}{}",
+            entry.size_human(),
+            entry.kind.to_string(),
+            entry.last_used.format("%Y-%m-%d"),
+            entry.path.display(),
+            if entry.stale { "  [stale]" } else { "" }
+        )
+    }
+}
+
+/// What to do once a selection has been made
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Next {
+    Clean,
+    Change,
+    Expand,
+    Cancel,
+}
+
+impl fmt::Display for Next {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Next::Clean => write!(f, "Clean the selected entries"),
+            Next::Change => write!(f, "Change the selection"),
+            Next::Expand => write!(f, "Show the biggest children of an entry"),
+            Next::Cancel => write!(f, "Cancel"),
+        }
+    }
+}
+
+/// Let the user choose which entries to clean, largest first with stale ones
+/// pre-selected. Returns `None` when the user cancels.
+pub fn pick_entries(entries: Vec<CacheEntry>) -> Result<Option<Vec<CacheEntry>>> {
+    let mut candidates: Vec<CacheEntry> = entries
+        .into_iter()
+        .filter(|e| !matches!(e.planned_action, Some(PlannedAction::Skip)))
+        .collect();
+    candidates.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
+    let mut selected: Vec<usize> = (0..candidates.len())
+        .filter(|&i| candidates[i].stale)
+        .collect();
+
+    loop {
+        let rows: Vec<Row> = candidates.iter().map(|entry| Row { entry }).collect();
+        let chosen = MultiSelect::new("Caches to clean:", rows)
+            .with_default(&selected)
+            .with_page_size(PAGE_SIZE)
+            .with_help_message("space toggles, → selects all, ← selects none, type to filter")
+            .with_formatter(&|chosen: &[ListOption<&Row>]| {
+                let total: u64 = chosen.iter().map(|o| o.value.entry.size_bytes).sum();
+                format!("{} entries, {} to free", chosen.len(), format_size(total))
+            })
+            .raw_prompt();
+        selected = match cancelled(chosen)? {
+            Some(chosen) => chosen.into_iter().map(|o| o.index).collect(),
+            None => return Ok(None),
+        };
+
+        loop {
+            let total: u64 = selected.iter().map(|&i| candidates[i].size_bytes).sum();
+            let prompt = format!(
+                "{} of {} entries selected, {} to free:",
+                selected.len(),
+                candidates.len(),
+                format_size(total)
+            );
+            let options = vec![Next::Clean, Next::Change, Next::Expand, Next::Cancel];
+            match cancelled(Select::new(&prompt, options).prompt())? {
+                Some(Next::Clean) => {
+                    selected.sort_unstable();
+                    let picked = selected.iter().map(|&i| candidates[i].clone()).collect();
+                    return Ok(Some(picked));
+                }
+                Some(Next::Change) => break,
+                Some(Next::Expand) => expand(&candidates)?,
+                Some(Next::Cancel) | None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Ask which entry to expand and print its biggest children
+fn expand(candidates: &[CacheEntry]) -> Result<()> {
+    let rows: Vec<Row> = candidates.iter().map(|entry| Row { entry }).collect();
+    let Some(row) = cancelled(
+        Select::new("Expand which entry?", rows)
+            .with_page_size(PAGE_SIZE)
+            .prompt(),
+    )?
+    else {
+        return Ok(());
+    };
+
+    let children = largest_children(&row.entry.path, CHILDREN_SHOWN);
+    if children.is_empty() {
+        println!("  (no children)");
+    }
+    for (path, size) in children {
+        let name = path.strip_prefix(&row.entry.path).unwrap_or(&path);
+        println!("  {:>10}  {}", format_size(size), name.display());
+    }
+    println!();
+    Ok(())
+}
+
+/// The `limit` largest direct children of `path`, largest first
+fn largest_children(path: &Path, limit: usize) -> Vec<(PathBuf, u64)> {
+    let mut children: Vec<(PathBuf, u64)> = fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|child| {
+            let path = child.path();
+            let size = util::get_size(&path).unwrap_or(0);
+            (path, size)
+        })
+        .collect();
+    children.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    children.truncate(limit);
+    children
+}
+
+/// Treat Esc and Ctrl-C as `None`; other prompt errors are real failures
+fn cancelled<T>(answer: std::result::Result<T, InquireError>) -> Result<Option<T>> {
+    match answer {
+        Ok(value) => Ok(Some(value)),
+        Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    humansize::format_size(bytes, humansize::DECIMAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_largest_children() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("big")).unwrap();
+        fs::write(root.join("big/blob"), vec![0u8; 4096]).unwrap();
+        fs::write(root.join("small"), b"x").unwrap();
+        fs::write(root.join("medium"), vec![0u8; 100]).unwrap();
+
+        let children = largest_children(root, 2);
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].0, root.join("big"));
+        assert_eq!(children[0].1, 4096);
+        assert_eq!(children[1].0, root.join("medium"));
+    }
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use crate::cache_entry::CacheKind;
+
+/// Assumed download speed when turning bytes into time (about 80 Mbit/s)
+const DOWNLOAD_BYTES_PER_SECOND: f64 = 10_000_000.0;
+
+/// Assumed cores available to a rebuild
+const BUILD_CORES: f64 = 4.0;
+
+/// CPU time spent unpacking and linking downloaded packages
+const UNPACK_BYTES_PER_CPU_SECOND: u64 = 50_000_000;
+
+/// Where a removed cache's contents come back from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegenSource {
+    /// Downloaded again: packages, models, images
+    Network,
+    /// Rebuilt locally: compiler output, bundles, bytecode
+    Build,
+}
+
+/// Estimated cost of getting a removed cache back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegenCost {
+    pub source: RegenSource,
+    /// Bytes that must come from the network again
+    pub network_bytes: u64,
+    /// Local CPU time to rebuild or unpack, in seconds
+    pub cpu_seconds: u64,
+}
+
+impl RegenCost {
+    /// Estimate from the cache's kind, where it comes back from and its size
+    pub fn estimate(kind: CacheKind, source: RegenSource, size_bytes: u64) -> Self {
+        match source {
+            RegenSource::Network => Self {
+                source,
+                network_bytes: size_bytes,
+                cpu_seconds: size_bytes / UNPACK_BYTES_PER_CPU_SECOND,
+            },
+            RegenSource::Build => Self {
+                source,
+                network_bytes: 0,
+                cpu_seconds: size_bytes / build_bytes_per_cpu_second(kind),
+            },
+        }
+    }
+
+    /// Rough wall-clock seconds to get the cache back, for ranking
+    pub fn seconds(&self) -> f64 {
+        self.network_bytes as f64 / DOWNLOAD_BYTES_PER_SECOND
+            + self.cpu_seconds as f64 / BUILD_CORES
+    }
+
+    /// Short form for tables, e.g. `1.2 GB download` or `~4m CPU`
+    pub fn describe(&self) -> String {
+        let cpu = format_duration(self.cpu_seconds);
+        match self.source {
+            RegenSource::Network if self.cpu_seconds >= 60 => format!(
+                "{} download, ~{} CPU",
+                humansize::format_size(self.network_bytes, humansize::DECIMAL),
+                cpu
+            ),
+            RegenSource::Network => format!(
+                "{} download",
+                humansize::format_size(self.network_bytes, humansize::DECIMAL)
+            ),
+            RegenSource::Build => format!("~{} CPU", cpu),
+        }
+    }
+}
+
+/// How much build output one CPU second produces, by ecosystem
+fn build_bytes_per_cpu_second(kind: CacheKind) -> u64 {
+    match kind {
+        // rustc and javac output is dense and slow to produce
+        CacheKind::Rust => 2_000_000,
+        CacheKind::Java => 5_000_000,
+        CacheKind::JavaScript | CacheKind::Python | CacheKind::Docker => 20_000_000,
+        CacheKind::MachineLearning | CacheKind::Npx => 50_000_000,
+        CacheKind::Generic => 10_000_000,
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m", seconds / 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_and_describe() {
+        let model = RegenCost::estimate(
+            CacheKind::MachineLearning,
+            RegenSource::Network,
+            3_000_000_000,
+        );
+        assert_eq!(model.network_bytes, 3_000_000_000);
+        assert_eq!(model.describe(), "3 GB download, ~1m CPU");
+
+        let target = RegenCost::estimate(CacheKind::Rust, RegenSource::Build, 2_000_000_000);
+        assert_eq!(target.network_bytes, 0);
+        assert_eq!(target.cpu_seconds, 1000);
+        assert_eq!(target.describe(), "~16m CPU");
+
+        // A 3 GB download costs more than rebuilding 200 MB of bundles
+        let bundle = RegenCost::estimate(CacheKind::JavaScript, RegenSource::Build, 200_000_000);
+        assert!(model.seconds() > bundle.seconds());
+    }
+}
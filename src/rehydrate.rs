@@ -0,0 +1,262 @@
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::cache_entry::CacheEntry;
+use crate::util;
+
+/// Regenerate commands run at once when `--rehydrate-jobs` is not given
+pub const DEFAULT_JOBS: usize = 4;
+
+/// One project's regenerate command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RehydrateTask {
+    pub project_root: PathBuf,
+    pub command: String,
+}
+
+/// How one regenerate command went
+#[derive(Debug, Clone, Serialize)]
+pub struct RehydrateOutcome {
+    pub project_root: PathBuf,
+    pub command: String,
+    pub success: bool,
+    /// Exit code, or `None` when the command could not start or was killed
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_secs: f64,
+}
+
+/// Every regenerate command run after a cleanup
+#[derive(Debug, Clone, Serialize)]
+pub struct RehydrateResult {
+    pub outcomes: Vec<RehydrateOutcome>,
+    /// File holding the output of every command
+    pub log_path: PathBuf,
+}
+
+impl RehydrateResult {
+    pub fn failed_count(&self) -> usize {
+        self.outcomes.iter().filter(|o| !o.success).count()
+    }
+}
+
+/// Commands to run for the entries at `cleaned`, once per project and command
+pub fn plan(entries: &[CacheEntry], cleaned: &[PathBuf]) -> Vec<RehydrateTask> {
+    let cleaned: HashSet<&Path> = cleaned.iter().map(PathBuf::as_path).collect();
+    let mut tasks = Vec::new();
+    for entry in entries {
+        if !cleaned.contains(entry.path.as_path()) {
+            continue;
+        }
+        let (Some(project_root), Some(command)) = (&entry.project_root, entry.rehydrate_command())
+        else {
+            continue;
+        };
+        let task = RehydrateTask {
+            project_root: project_root.clone(),
+            command: command.to_string(),
+        };
+        if !tasks.contains(&task) {
+            tasks.push(task);
+        }
+    }
+    tasks
+}
+
+/// Log file for a rehydrate run, next to the backups
+pub fn log_path() -> PathBuf {
+    util::get_backup_dir().join(format!("rehydrate-{}.log", util::create_backup_dir_name()))
+}
+
+/// Run `tasks`, at most `jobs` at a time, appending their output to `log_path`
+pub fn run(tasks: &[RehydrateTask], jobs: usize, log_path: &Path) -> Result<RehydrateResult> {
+    if let Some(parent) = log_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .with_context(|| format!("Failed to open {}", log_path.display()))?;
+    let log = Mutex::new(log);
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.max(1))
+        .build()
+        .context("Failed to start rehydrate workers")?;
+    let outcomes = pool.install(|| tasks.par_iter().map(|task| run_task(task, &log)).collect());
+
+    Ok(RehydrateResult {
+        outcomes,
+        log_path: log_path.to_path_buf(),
+    })
+}
+
+fn run_task(task: &RehydrateTask, log: &Mutex<File>) -> RehydrateOutcome {
+    let started = Instant::now();
+    let output = shell(&task.command)
+        .current_dir(&task.project_root)
+        .output();
+    let duration_secs = started.elapsed().as_secs_f64();
+
+    // Each command's output is written in one piece so parallel runs do not interleave
+    let mut record = format!("==> {} $ {}\n", task.project_root.display(), task.command);
+    let outcome = match output {
+        Ok(output) => {
+            record.push_str(&String::from_utf8_lossy(&output.stdout));
+            record.push_str(&String::from_utf8_lossy(&output.stderr));
+            record.push_str(&format!(
+                "<== {} in {:.1}s\n\n",
+                output.status, duration_secs
+            ));
+            RehydrateOutcome {
+                project_root: task.project_root.clone(),
+                command: task.command.clone(),
+                success: output.status.success(),
+                exit_code: output.status.code(),
+                error: None,
+                duration_secs,
+            }
+        }
+        Err(e) => {
+            record.push_str(&format!("<== failed to start: {}\n\n", e));
+            RehydrateOutcome {
+                project_root: task.project_root.clone(),
+                command: task.command.clone(),
+                success: false,
+                exit_code: None,
+                error: Some(e.to_string()),
+                duration_secs,
+            }
+        }
+    };
+    if let Ok(mut log) = log.lock() {
+        let _ = log.write_all(record.as_bytes());
+    }
+    outcome
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache_entry::CacheKind;
+    use crate::config::MergedConfig;
+    use crate::detectors::DetectorRegistry;
+    use crate::discover::DiscoveryResult;
+    use crate::inspect::CacheInspector;
+    use tempfile::TempDir;
+
+    fn entry(path: &Path, root: &Path, command: &str) -> CacheEntry {
+        let mut entry = CacheEntry::new(
+            path.to_path_buf(),
+            CacheKind::JavaScript,
+            0,
+            chrono::Utc::now(),
+            false,
+        )
+        .with_regenerate(Some(command.to_string()));
+        entry.project_root = Some(root.to_path_buf());
+        entry
+    }
+
+    #[test]
+    fn test_plan_dedupes_and_skips_uncleaned() {
+        let root = Path::new("/p");
+        let entries = vec![
+            entry(&root.join("node_modules"), root, "npm ci"),
+            entry(&root.join(".next"), root, "npm ci"),
+            entry(&root.join("dist"), root, "npm ci"),
+            entry(Path::new("/q/target"), Path::new("/q"), "cargo build"),
+        ];
+        let cleaned = vec![root.join("node_modules"), root.join(".next")];
+
+        let tasks = plan(&entries, &cleaned);
+        assert_eq!(
+            tasks,
+            vec![RehydrateTask {
+                project_root: root.to_path_buf(),
+                command: "npm ci".to_string(),
+            }]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_plan_uses_the_detector_rehydrate_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("Cargo.toml"), "[package]").unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+
+        let config = MergedConfig {
+            min_size: 0,
+            ..Default::default()
+        };
+        let discovered =
+            DiscoveryResult::discover_in(root.to_path_buf(), &DetectorRegistry::builtin(), &config)
+                .unwrap();
+        let entries = CacheInspector::new(config)
+            .inspect_caches(&discovered.cache_entries)
+            .unwrap();
+
+        let tasks = plan(&entries, &[root.join("target")]);
+        assert_eq!(
+            tasks,
+            vec![RehydrateTask {
+                project_root: root.to_path_buf(),
+                command: "cargo fetch".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_run_logs_output_and_reports_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("logs/rehydrate.log");
+        let tasks = vec![
+            RehydrateTask {
+                project_root: temp_dir.path().to_path_buf(),
+                command: "echo restored > marker && echo done".to_string(),
+            },
+            RehydrateTask {
+                project_root: temp_dir.path().to_path_buf(),
+                command: "echo broken >&2; exit 3".to_string(),
+            },
+        ];
+
+        let result = run(&tasks, 2, &log_path).unwrap();
+        assert_eq!(result.outcomes.len(), 2);
+        assert!(result.outcomes[0].success);
+        assert_eq!(result.outcomes[1].exit_code, Some(3));
+        assert_eq!(result.failed_count(), 1);
+        assert!(temp_dir.path().join("marker").exists());
+
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("done"));
+        assert!(log.contains("broken"));
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cache_entry::{CacheEntry, CacheKind, PlannedAction};
+use crate::util::{matches_any_glob, parse_days, parse_size};
+
+/// A cleanup policy from `[[rules]]` in `.cachekillrc`.
+/// Every condition that is set must hold for the rule to match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Shown by `--explain`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<CacheKind>,
+    /// Glob matched against the cache path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Detector that recognized the owning project (`javascript`, `rust`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_type: Option<String>,
+    /// Package manager owning the cache (`npm`, `pnpm`, `yarn`, `bun`, `deno`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manager: Option<String>,
+    /// Cache unchanged for longer than this (`30d`, `2w`, `6m`, `1y`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub older_than: Option<String>,
+    /// Cache bigger than this (`500MB`, `2GiB`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub larger_than: Option<String>,
+    /// Owning project inactive for longer than this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_inactive_for: Option<String>,
+    pub action: PlannedAction,
+    /// Higher priorities are evaluated first; ties keep file order
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl Rule {
+    /// Check the rule's thresholds parse
+    pub fn validate(&self) -> Result<()> {
+        if let Some(age) = &self.older_than {
+            parse_days(age).context("older_than")?;
+        }
+        if let Some(age) = &self.project_inactive_for {
+            parse_days(age).context("project_inactive_for")?;
+        }
+        if let Some(size) = &self.larger_than {
+            parse_size(size).context("larger_than")?;
+        }
+        Ok(())
+    }
+
+    /// Check whether every condition holds for `entry`
+    pub fn matches(&self, entry: &CacheEntry) -> bool {
+        // Only caches that can be trimmed in place can be pruned
+        if self.action == PlannedAction::Prune && entry.reclaimable_bytes.is_none() {
+            return false;
+        }
+        if self.kind.is_some_and(|kind| kind != entry.kind) {
+            return false;
+        }
+        if let Some(glob) = &self.path {
+            if !matches_any_glob(&entry.path, std::slice::from_ref(glob)) {
+                return false;
+            }
+        }
+        if let Some(project_type) = &self.project_type {
+            if !entry.project_types.contains(project_type) {
+                return false;
+            }
+        }
+        if let Some(manager) = &self.manager {
+            if entry.manager.as_ref() != Some(manager) {
+                return false;
+            }
+        }
+        if let Some(age) = self.older_than.as_deref().and_then(|a| parse_days(a).ok()) {
+            if (chrono::Utc::now() - entry.last_used).num_days() <= i64::from(age) {
+                return false;
+            }
+        }
+        if let Some(size) = self.larger_than.as_deref().and_then(|s| parse_size(s).ok()) {
+            if entry.size_bytes <= size {
+                return false;
+            }
+        }
+        if let Some(age) = self
+            .project_inactive_for
+            .as_deref()
+            .and_then(|a| parse_days(a).ok())
+        {
+            let Some(last_active) = entry.project_last_active else {
+                return false;
+            };
+            if (chrono::Utc::now() - last_active).num_days() <= i64::from(age) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// How `--explain` refers to the rule
+    fn label(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => format!("rule \"{}\"", name),
+            None => format!("rule #{}", index + 1),
+        }
+    }
+}
+
+/// Rules in evaluation order
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<(usize, Rule)>,
+}
+
+impl RuleSet {
+    pub fn new(rules: &[Rule]) -> Self {
+        let mut rules: Vec<(usize, Rule)> = rules.iter().cloned().enumerate().collect();
+        rules.sort_by_key(|(_, rule)| std::cmp::Reverse(rule.priority));
+        Self { rules }
+    }
+
+    /// The first matching rule's action, with a description of the rule
+    pub fn decide(&self, entry: &CacheEntry) -> Option<(PlannedAction, String)> {
+        self.rules
+            .iter()
+            .find(|(_, rule)| rule.matches(entry))
+            .map(|(index, rule)| (rule.action, rule.label(*index)))
+    }
+
+    /// Apply the rules to entries that were not refused for safety reasons
+    pub fn apply(&self, entries: &mut [CacheEntry]) {
+        for entry in entries {
+            if entry.skip_reason.is_some() {
+                continue;
+            }
+            if let Some((action, decided_by)) = self.decide(entry) {
+                entry.planned_action = Some(action);
+                entry.decided_by = Some(decided_by);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use std::path::PathBuf;
+
+    fn node_modules(size: u64, inactive_days: i64) -> CacheEntry {
+        let mut entry = CacheEntry::new(
+            PathBuf::from("/work/app/node_modules"),
+            CacheKind::JavaScript,
+            size,
+            Utc::now(),
+            false,
+        )
+        .with_project_last_active(Some(Utc::now() - Duration::days(inactive_days)));
+        entry.project_types = vec!["javascript".to_string()];
+        entry
+    }
+
+    fn rules(toml_rules: &str) -> RuleSet {
+        #[derive(Deserialize)]
+        struct File {
+            rules: Vec<Rule>,
+        }
+        let file: File = toml::from_str(toml_rules).unwrap();
+        for rule in &file.rules {
+            rule.validate().unwrap();
+        }
+        RuleSet::new(&file.rules)
+    }
+
+    #[test]
+    fn test_project_inactive_rule() {
+        let set = rules(
+            r#"
+[[rules]]
+name = "abandoned node_modules"
+path = "**/node_modules"
+project_type = "javascript"
+project_inactive_for = "60d"
+action = "delete"
+"#,
+        );
+        assert_eq!(
+            set.decide(&node_modules(10, 90)),
+            Some((
+                PlannedAction::Delete,
+                "rule \"abandoned node_modules\"".to_string()
+            ))
+        );
+        assert_eq!(set.decide(&node_modules(10, 5)), None);
+    }
+
+    #[test]
+    fn test_priority_and_safety() {
+        let set = rules(
+            r#"
+[[rules]]
+kind = "js"
+action = "backup"
+
+[[rules]]
+larger_than = "1GB"
+action = "skip"
+priority = 10
+
+[[rules]]
+kind = "js"
+action = "prune"
+priority = 20
+"#,
+        );
+        // The prune rule cannot apply to a cache without prune support
+        let (action, by) = set.decide(&node_modules(2_000_000_000, 0)).unwrap();
+        assert_eq!(action, PlannedAction::Skip);
+        assert_eq!(by, "rule #2");
+        assert_eq!(
+            set.decide(&node_modules(10, 0)).unwrap().0,
+            PlannedAction::Backup
+        );
+
+        // Entries refused for safety keep their skip
+        let mut refused = node_modules(10, 0).with_skip_reason(Some("tracked".to_string()));
+        refused.planned_action = Some(PlannedAction::Skip);
+        let mut entries = vec![refused];
+        set.apply(&mut entries);
+        assert_eq!(entries[0].planned_action, Some(PlannedAction::Skip));
+    }
+
+    #[test]
+    fn test_invalid_threshold() {
+        let rule = Rule {
+            name: None,
+            kind: None,
+            path: None,
+            project_type: None,
+            manager: None,
+            older_than: Some("soon".to_string()),
+            larger_than: None,
+            project_inactive_for: None,
+            action: PlannedAction::Delete,
+            priority: 0,
+        };
+        assert!(rule.validate().is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::activity::{ActivitySignal, ProjectActivity};
+use crate::cache_entry::{CacheEntry, PlannedAction};
+use crate::config::MergedConfig;
+use crate::detectors::DetectorRegistry;
+use crate::discover::DiscoveryResult;
+use crate::inspect::CacheInspector;
+use crate::util::get_mtime;
+
+/// Default number of levels below each root searched for projects
+pub const DEFAULT_DEPTH: usize = 6;
+
+/// One project found by a scan, with its caches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScannedProject {
+    pub root: PathBuf,
+    /// Detectors whose markers matched the project root
+    pub detectors: Vec<String>,
+    pub size_bytes: u64,
+    /// When the project was last worked on
+    pub last_touched: DateTime<Utc>,
+    /// The signal that decided `last_touched` (git commit, lockfile, ...)
+    pub activity_signal: ActivitySignal,
+    pub days_inactive: i64,
+    pub caches: Vec<CacheEntry>,
+}
+
+impl ScannedProject {
+    pub fn size_human(&self) -> String {
+        humansize::format_size(self.size_bytes, humansize::DECIMAL)
+    }
+}
+
+/// Find every project below `roots` and inspect its caches in parallel.
+/// Projects are returned largest first; projects without caches are dropped.
+pub fn scan(roots: &[PathBuf], depth: usize, config: &MergedConfig) -> Result<Vec<ScannedProject>> {
+    let registry = DetectorRegistry::load();
+    let cache_names = cache_dir_names(&registry);
+
+    let mut project_roots: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("Cannot scan {}", root.display()))?;
+        for project in find_projects(&root, depth, &registry, &cache_names) {
+            if !project_roots.contains(&project) {
+                project_roots.push(project);
+            }
+        }
+    }
+
+    let progress = if config.json {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(project_roots.len() as u64)
+    };
+    progress.set_style(
+        ProgressStyle::with_template(
+            "{spinner} Scanning projects [{bar:30}] {pos}/{len} {wide_msg}",
+        )
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars("=> "),
+    );
+
+    let inspector = CacheInspector::new(config.clone());
+    let mut projects: Vec<ScannedProject> = project_roots
+        .par_iter()
+        .filter_map(|root| {
+            progress.set_message(root.display().to_string());
+            let project = scan_project(root, &registry, &inspector, config);
+            progress.inc(1);
+            match project {
+                Ok(project) => Some(project),
+                Err(e) => {
+                    progress.suspend(|| eprintln!("Warning: skipping {}: {:#}", root.display(), e));
+                    None
+                }
+            }
+        })
+        .collect();
+    progress.finish_and_clear();
+
+    // A nested project keeps its own caches; its parent does not count them again
+    projects.sort_by_key(|p| std::cmp::Reverse(p.root.components().count()));
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    for project in &mut projects {
+        project.caches.retain(|c| seen.insert(c.path.clone()));
+        project.size_bytes = project.caches.iter().map(|c| c.size_bytes).sum();
+    }
+
+    projects.retain(|p| !p.caches.is_empty());
+    projects.sort_by_key(|p| std::cmp::Reverse(p.size_bytes));
+    Ok(projects)
+}
+
+/// Caches of projects untouched for at least `days`, ready to clean
+pub fn inactive_entries(projects: &[ScannedProject], days: i64) -> Vec<CacheEntry> {
+    projects
+        .iter()
+        .filter(|p| p.days_inactive >= days)
+        .flat_map(|p| p.caches.iter().cloned())
+        .filter(|c| !matches!(c.planned_action, Some(PlannedAction::Skip)))
+        .collect()
+}
+
+/// Inspect the caches inside one project
+fn scan_project(
+    root: &Path,
+    registry: &DetectorRegistry,
+    inspector: &CacheInspector,
+    config: &MergedConfig,
+) -> Result<ScannedProject> {
+    let discovery = DiscoveryResult::discover_in(root.to_path_buf(), registry, config)?;
+    // Global caches are shared by every project and are not scanned per project
+    let local: Vec<_> = discovery
+        .cache_entries
+        .into_iter()
+        .filter(|c| c.path.starts_with(root))
+        .collect();
+    let caches = inspector.inspect_caches(&local)?;
+
+    let activity = match discovery.activity {
+        Some(activity) => activity,
+        None => ProjectActivity {
+            last_active: get_mtime(root)?,
+            signal: ActivitySignal::FileTree,
+        },
+    };
+    let detectors = registry
+        .matching(root)
+        .iter()
+        .filter(|d| !d.requires_all)
+        .map(|d| d.name.clone())
+        .collect();
+
+    Ok(ScannedProject {
+        root: root.to_path_buf(),
+        detectors,
+        size_bytes: caches.iter().map(|c| c.size_bytes).sum(),
+        days_inactive: activity.inactive_days(),
+        last_touched: activity.last_active,
+        activity_signal: activity.signal,
+        caches,
+    })
+}
+
+/// Directories under `root` (up to `depth` levels) that a detector recognizes.
+/// Hidden directories and cache directories are not searched.
+fn find_projects(
+    root: &Path,
+    depth: usize,
+    registry: &DetectorRegistry,
+    cache_names: &HashSet<String>,
+) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(root)
+        .max_depth(depth)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            let name = entry.file_name().to_string_lossy();
+            entry.file_type().is_dir()
+                && !name.starts_with('.')
+                && !cache_names.contains(name.as_ref())
+        })
+        .flatten()
+        .filter(|entry| {
+            registry
+                .matching(entry.path())
+                .iter()
+                .any(|d| !d.requires_all)
+        })
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Top-level directory names detectors treat as caches (`node_modules`, `target`, ...)
+fn cache_dir_names(registry: &DetectorRegistry) -> HashSet<String> {
+    registry
+        .detectors()
+        .iter()
+        .flat_map(|d| d.caches.iter().chain(d.nested.iter()))
+        .filter_map(|cache| cache.split('/').next())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_projects_skips_caches_and_hidden_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for (dir, marker) in [
+            ("web", "package.json"),
+            ("web/node_modules/left-pad", "package.json"),
+            ("tools/cli", "Cargo.toml"),
+            (".hidden/app", "package.json"),
+            ("notes", "README.md"),
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join(marker), "").unwrap();
+        }
+
+        let registry = DetectorRegistry::builtin();
+        let mut projects =
+            find_projects(root, DEFAULT_DEPTH, &registry, &cache_dir_names(&registry));
+        projects.sort();
+        assert_eq!(projects, vec![root.join("tools/cli"), root.join("web")]);
+
+        // Depth limits how far below the root projects are looked for
+        let shallow = find_projects(root, 1, &registry, &cache_dir_names(&registry));
+        assert_eq!(shallow, vec![root.join("web")]);
+    }
+
+    #[test]
+    fn test_inactive_entries() {
+        let entry = |path: &str, action| {
+            CacheEntry::new(
+                PathBuf::from(path),
+                crate::cache_entry::CacheKind::JavaScript,
+                10,
+                Utc::now(),
+                false,
+            )
+            .with_planned_action(action)
+        };
+        let project = |days_inactive, caches| ScannedProject {
+            root: PathBuf::from("/p"),
+            detectors: vec![],
+            size_bytes: 0,
+            last_touched: Utc::now(),
+            activity_signal: ActivitySignal::FileTree,
+            days_inactive,
+            caches,
+        };
+        let projects = vec![
+            project(
+                120,
+                vec![
+                    entry("/old/node_modules", PlannedAction::Backup),
+                    entry("/old/build", PlannedAction::Skip),
+                ],
+            ),
+            project(3, vec![entry("/new/node_modules", PlannedAction::Backup)]),
+        ];
+
+        let entries = inactive_entries(&projects, 90);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, PathBuf::from("/old/node_modules"));
+    }
+}
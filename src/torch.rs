@@ -85,7 +85,7 @@ impl TorchCacheManager {
         }
 
         // Sort by size descending
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         Ok(entries)
     }
@@ -139,14 +139,16 @@ impl TorchCacheManager {
                 PlannedAction::Skip
             };
 
-            cache_entries.push(CacheEntry {
-                path: entry.path,
-                kind: CacheKind::MachineLearning,
-                size_bytes: entry.size_bytes,
-                last_used: entry.last_used,
-                stale: is_stale,
-                planned_action: Some(planned_action),
-            });
+            cache_entries.push(
+                CacheEntry::new(
+                    entry.path,
+                    CacheKind::MachineLearning,
+                    entry.size_bytes,
+                    entry.last_used,
+                    is_stale,
+                )
+                .with_planned_action(planned_action),
+            );
         }
 
         Ok(cache_entries)
@@ -161,7 +163,7 @@ impl TorchCacheManager {
         let mut version = None;
 
         // Look for common PyTorch cache patterns
-        for (_i, part) in parts.iter().enumerate() {
+        for part in parts.iter() {
             if *part == "checkpoints" {
                 cache_type = "checkpoints".to_string();
             } else if *part == "hub" {
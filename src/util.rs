@@ -1,6 +1,5 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Datelike, Timelike, Utc};
-use dirs;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -23,9 +22,10 @@ pub fn normalize_path(path: &Path) -> Result<PathBuf> {
 /// Expand home directory in a path string
 #[allow(dead_code)]
 pub fn expand_home(path: &str) -> PathBuf {
-    if path.starts_with("~") {
+    if let Some(rest) = path.strip_prefix('~') {
         if let Some(home) = dirs::home_dir() {
-            home.join(path.strip_prefix("~").unwrap())
+            // `~/x` must join as `x`, or the absolute `/x` would replace the home directory
+            home.join(rest.trim_start_matches(['/', '\\']))
         } else {
             PathBuf::from(path)
         }
@@ -133,6 +133,25 @@ pub fn create_backup_dir_name() -> String {
     )
 }
 
+/// Create the directory `name` in `parent`, adding `-2`, `-3`, ... when the name is taken
+pub fn create_unique_dir(parent: &Path, name: &str) -> Result<PathBuf> {
+    fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    let mut path = parent.join(name);
+    let mut n = 2;
+    loop {
+        match fs::create_dir(&path) {
+            Ok(()) => return Ok(path),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                path = parent.join(format!("{}-{}", name, n));
+                n += 1;
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to create {}", path.display()))
+            }
+        }
+    }
+}
+
 /// Check if a path matches any of the given glob patterns
 pub fn matches_any_glob(path: &Path, patterns: &[String]) -> bool {
     use globset::{Glob, GlobSetBuilder};
@@ -164,6 +183,89 @@ pub fn should_include_path(path: &Path, include_patterns: &[String]) -> bool {
     matches_any_glob(path, include_patterns)
 }
 
+/// Parse a human size such as `500MB`, `2GiB` or `1024` (bytes)
+pub fn parse_size(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid size: {}", input))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        other => anyhow::bail!("Unknown size unit '{}' in {}", other, input),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Parse a duration in days such as `30`, `30d`, `2w`, `6m` (30-day months) or `1y`
+pub fn parse_days(input: &str) -> Result<u32> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: u32 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid duration: {}", input))?;
+    let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "d" | "day" | "days" => 1,
+        "w" | "week" | "weeks" => 7,
+        "m" | "mo" | "month" | "months" => 30,
+        "y" | "year" | "years" => 365,
+        other => anyhow::bail!("Unknown duration unit '{}' in {}", other, input),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow::anyhow!("Duration too large: {}", input))
+}
+
+/// The candidate closest to a misspelled `name`, if any is close enough
+pub fn suggest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (name.len() / 3).max(2);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+        // A truncated name such as `older` for `older_than`
+        .or_else(|| {
+            let mut prefixed = candidates
+                .iter()
+                .filter(|candidate| name.len() >= 3 && candidate.starts_with(name));
+            match (prefixed.next(), prefixed.next()) {
+                (Some(candidate), None) => Some(*candidate),
+                _ => None,
+            }
+        })
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,9 +274,10 @@ mod tests {
 
     #[test]
     fn test_expand_home() {
-        let _home = dirs::home_dir().unwrap();
+        let home = dirs::home_dir().unwrap();
         let expanded = expand_home("~/test");
         assert!(expanded.to_string_lossy().contains("test"));
+        assert_eq!(expanded, home.join("test"));
     }
 
     #[test]
@@ -217,6 +320,17 @@ mod tests {
         assert!(name.contains("_"));
     }
 
+    #[test]
+    fn test_create_unique_dir_within_the_same_second() {
+        let temp_dir = TempDir::new().unwrap();
+        let name = create_backup_dir_name();
+        let first = create_unique_dir(temp_dir.path(), &name).unwrap();
+        let second = create_unique_dir(temp_dir.path(), &name).unwrap();
+        assert_eq!(first, temp_dir.path().join(&name));
+        assert_eq!(second, temp_dir.path().join(format!("{}-2", name)));
+        assert!(second.is_dir());
+    }
+
     #[test]
     fn test_matches_any_glob() {
         let path = PathBuf::from("/tmp/test.txt");
@@ -227,4 +341,39 @@ mod tests {
         let patterns2 = vec!["*.log".to_string(), "*.json".to_string()];
         assert!(!matches_any_glob(&path, &patterns2));
     }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("500MB").unwrap(), 500_000_000);
+        assert_eq!(parse_size("1.5 GB").unwrap(), 1_500_000_000);
+        assert_eq!(parse_size("2GiB").unwrap(), 2 << 30);
+        assert!(parse_size("ten MB").is_err());
+        assert!(parse_size("5 parsecs").is_err());
+    }
+
+    #[test]
+    fn test_suggest() {
+        let keys = ["exclude_paths", "include_paths", "stale_days"];
+        assert_eq!(suggest("exlude_paths", &keys), Some("exclude_paths"));
+        assert_eq!(suggest("stale_day", &keys), Some("stale_days"));
+        assert_eq!(suggest("colour", &keys), None);
+        assert_eq!(
+            suggest("older", &["older_than", "larger_than"]),
+            Some("older_than")
+        );
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("30").unwrap(), 30);
+        assert_eq!(parse_days("60d").unwrap(), 60);
+        assert_eq!(parse_days("2w").unwrap(), 14);
+        assert_eq!(parse_days("6m").unwrap(), 180);
+        assert_eq!(parse_days("1 year").unwrap(), 365);
+        assert!(parse_days("soon").is_err());
+        assert!(parse_days("3 fortnights").is_err());
+        let err = parse_days("20000000y").unwrap_err();
+        assert!(err.to_string().contains("too large"));
+    }
 }
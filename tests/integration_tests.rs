@@ -30,7 +30,7 @@ fn run_cachekill(args: &[&str]) -> std::process::Output {
     let mut cmd = Command::new(&binary_path);
 
     if binary_path == "cargo" {
-        cmd.args(&["run", "--"]);
+        cmd.args(["run", "--"]);
         cmd.args(args);
     } else {
         cmd.args(args);
@@ -276,3 +276,667 @@ fn test_npx_integration() {
             || stdout.contains("entries")
     );
 }
+
+fn run_cachekill_in(dir: &Path, args: &[&str]) -> serde_json::Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    // `--list --json` prints the summary as a second document
+    serde_json::Deserializer::from_slice(&output.stdout)
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn test_git_aware_safety() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(args)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    };
+    if !git(&["init", "-q"]) {
+        return;
+    }
+
+    std::fs::write(
+        root.join("package.json"),
+        r#"{"dependencies":{"react-scripts":"5.0.1"}}"#,
+    )
+    .unwrap();
+    std::fs::write(root.join(".gitignore"), "node_modules/\nbuild/\nlogs/\n").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/react")).unwrap();
+    std::fs::write(root.join("node_modules/react/index.js"), vec![b'x'; 2048]).unwrap();
+    std::fs::create_dir_all(root.join("logs")).unwrap();
+    std::fs::write(root.join("logs/app.log"), vec![b'x'; 4096]).unwrap();
+    // Ignored, but a file inside was force-added
+    std::fs::create_dir_all(root.join("build")).unwrap();
+    std::fs::write(root.join("build/deploy.sh"), "echo deploy").unwrap();
+    assert!(git(&["add", "-f", "build/deploy.sh"]));
+
+    let json = run_cachekill_in(root, &["--dry-run", "--json"]);
+    let to_skip = json["to_skip"].as_array().unwrap();
+    assert_eq!(to_skip.len(), 1);
+    assert!(to_skip[0]["path"].as_str().unwrap().ends_with("build"));
+    assert_eq!(to_skip[0]["skip_reason"], "contains 1 git-tracked file");
+    let to_backup = json["to_backup"].as_array().unwrap();
+    assert_eq!(to_backup.len(), 1);
+    assert!(to_backup[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("node_modules"));
+    assert_eq!(to_backup[0]["confidence"], "high");
+
+    // --gitignored proposes every ignored directory over the threshold, largest first
+    let json = run_cachekill_in(
+        root,
+        &["--gitignored", "--min-size", "1KB", "--list", "--json"],
+    );
+    let paths: Vec<&str> = json["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths.len(), 2);
+    assert!(paths[0].ends_with("logs"));
+    assert!(paths[1].ends_with("node_modules"));
+}
+
+#[test]
+fn test_cachedir_tag_discovery_and_creation() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/react")).unwrap();
+    std::fs::write(root.join("node_modules/react/index.js"), "x").unwrap();
+    std::fs::create_dir_all(root.join("tools/renders")).unwrap();
+    std::fs::write(
+        root.join("tools/renders/CACHEDIR.TAG"),
+        "Signature: 8a477f597d28d172789f06886806bc55\n",
+    )
+    .unwrap();
+
+    let json = run_cachekill_in(root, &["--list", "--json"]);
+    let paths: Vec<&str> = json["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths.len(), 2);
+    assert!(paths.iter().any(|p| p.ends_with("renders")));
+
+    let json = run_cachekill_in(root, &["--json", "tag"]);
+    assert_eq!(json["tagged"].as_array().unwrap().len(), 1);
+    assert_eq!(json["already_tagged"].as_array().unwrap().len(), 1);
+    let tag = std::fs::read_to_string(root.join("node_modules/CACHEDIR.TAG")).unwrap();
+    assert!(tag.starts_with("Signature: 8a477f597d28d172789f06886806bc55"));
+}
+
+#[test]
+fn test_scan_aggregates_projects_across_roots() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    for (project, marker, cache, size) in [
+        ("code/web", "package.json", "node_modules/react", 3000),
+        ("work/api", "requirements.txt", ".venv/lib", 1000),
+        (
+            "work/api/frontend",
+            "package.json",
+            "node_modules/vue",
+            2000,
+        ),
+    ] {
+        std::fs::create_dir_all(root.join(project).join(cache)).unwrap();
+        std::fs::write(root.join(project).join(marker), "{}").unwrap();
+        std::fs::write(
+            root.join(project).join(cache).join("blob"),
+            vec![b'x'; size],
+        )
+        .unwrap();
+    }
+
+    let json = run_cachekill_in(root, &["scan", "code", "work", "--json"]);
+    let projects = json["projects"].as_array().unwrap();
+    let summary: Vec<(&str, u64)> = projects
+        .iter()
+        .map(|p| {
+            (
+                p["root"].as_str().unwrap(),
+                p["size_bytes"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(summary.len(), 3);
+    // Largest first, and the nested project's caches are not counted twice
+    assert!(summary[0].0.ends_with("web") && summary[0].1 == 3000);
+    assert!(summary[1].0.ends_with("frontend") && summary[1].1 == 2000);
+    assert!(summary[2].0.ends_with("api") && summary[2].1 == 1000);
+    assert_eq!(json["total_size_bytes"], 6000);
+}
+
+#[test]
+fn test_project_npmrc_is_read_from_the_project_root() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::write(root.join(".npmrc"), "cache=/srv/npm-cache\n").unwrap();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args(["--json", "list", "--js-pm"])
+        .current_dir(root.join("src"))
+        .env_remove("npm_config_cache")
+        .env_remove("NPM_CONFIG_CACHE")
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let locations = serde_json::Deserializer::from_slice(&output.stdout)
+        .into_iter::<serde_json::Value>()
+        .map(Result::unwrap)
+        .find(|doc| doc.get("package_managers").is_some())
+        .expect("package manager locations");
+    let npm_cache = locations["package_managers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|l| l["manager"] == "npm" && l["label"] == "cache")
+        .unwrap();
+    assert_eq!(npm_cache["path"], "/srv/npm-cache");
+    assert_eq!(
+        npm_cache["source"],
+        format!("{} (cache)", root.join(".npmrc").display())
+    );
+}
+
+#[test]
+fn test_rules_decide_planned_actions() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/react")).unwrap();
+    std::fs::write(root.join("node_modules/react/index.js"), "x").unwrap();
+    std::fs::write(
+        root.join(".cachekillrc"),
+        r#"
+[[rules]]
+name = "throwaway deps"
+path = "**/node_modules"
+project_type = "javascript"
+action = "delete"
+"#,
+    )
+    .unwrap();
+
+    let json = run_cachekill_in(root, &["--dry-run", "--json", "--explain"]);
+    let to_delete = json["to_delete"].as_array().unwrap();
+    assert_eq!(to_delete.len(), 1);
+    assert_eq!(to_delete[0]["decided_by"], "rule \"throwaway deps\"");
+    assert_eq!(to_delete[0]["detector"], "javascript");
+    assert!(json["to_backup"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_layered_config_origins() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let xdg = root.join("xdg");
+    std::fs::create_dir_all(xdg.join("cachekill")).unwrap();
+    std::fs::write(
+        xdg.join("cachekill/config.toml"),
+        "stale_days = 30\nsafe_delete = false\nexclude_paths = [\"**/vendor\"]\n",
+    )
+    .unwrap();
+    let project = root.join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(
+        project.join(".cachekillrc"),
+        "stale_days = 7\nexclude_paths = [\"**/dist\"]\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args(["--stale-days", "3", "config", "show", "--origin", "--json"])
+        .current_dir(&project)
+        .env("XDG_CONFIG_HOME", &xdg)
+        .env("CACHEKILL_SAFE_DELETE", "yes")
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let values: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    let get = |key: &str| values.iter().find(|v| v["key"] == key).unwrap().clone();
+
+    assert_eq!(get("stale_days")["value"], 3);
+    assert_eq!(get("stale_days")["origin"], "cli (--stale-days)");
+    assert_eq!(get("safe_delete")["value"], true);
+    assert_eq!(get("safe_delete")["origin"], "env (CACHEKILL_SAFE_DELETE)");
+    let exclude = get("exclude_paths");
+    let exclude: Vec<&str> = exclude["value"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    assert!(exclude.contains(&"**/vendor") && exclude.contains(&"**/dist"));
+    assert!(get("exclude_paths")["origin"]
+        .as_str()
+        .unwrap()
+        .starts_with("default, user ("));
+}
+
+#[test]
+fn test_invalid_config_aborts() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join(".cachekillrc"), "exlude_paths = [\"**/dist\"]\n").unwrap();
+
+    for args in [&["config", "check"][..], &["--dry-run"][..]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+            .args(args)
+            .current_dir(root)
+            .output()
+            .expect("Failed to execute command");
+        assert_eq!(output.status.code(), Some(4));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(".cachekillrc:1:1: unknown key `exlude_paths`"));
+        assert!(stderr.contains("did you mean `exclude_paths`?"));
+    }
+}
+
+#[test]
+fn test_init_writes_config_once() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+
+    let init = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cachekill"))
+            .arg("init")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let json = run_cachekill_in(root, &["init", "--json"]);
+    assert_eq!(json["written"], true);
+    assert_eq!(json["plan"]["managers"][0], "pnpm");
+    let written = std::fs::read_to_string(root.join(".cachekillrc")).unwrap();
+    assert!(written.contains("# Package managers: pnpm"));
+
+    // An existing file is only replaced with --force
+    std::fs::write(root.join(".cachekillrc"), "stale_days = 3\n").unwrap();
+    assert!(!init(&[]).status.success());
+    assert_eq!(
+        std::fs::read_to_string(root.join(".cachekillrc")).unwrap(),
+        "stale_days = 3\n"
+    );
+    assert!(init(&["--force"]).status.success());
+    assert_eq!(
+        std::fs::read_to_string(root.join(".cachekillrc")).unwrap(),
+        written
+    );
+
+    let check = run_cachekill_in(root, &["config", "check", "--json"]);
+    assert_eq!(check["valid"], true);
+}
+
+#[test]
+fn test_subcommands_and_legacy_flags() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+    std::fs::write(root.join("node_modules/left-pad/index.js"), "x").unwrap();
+
+    let cachekill = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cachekill"))
+            .args(args)
+            .current_dir(root)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // The subcommand and the deprecated flag list the same entries
+    let listed = run_cachekill_in(root, &["list", "--json"]);
+    let legacy = cachekill(&["--list", "--json"]);
+    assert!(legacy.status.success());
+    assert!(String::from_utf8_lossy(&legacy.stderr).contains("deprecated"));
+    let legacy: serde_json::Value = serde_json::Deserializer::from_slice(&legacy.stdout)
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(listed["entries"], legacy["entries"]);
+
+    // `plan` never deletes
+    let plan = cachekill(&["plan"]);
+    assert!(plan.status.success());
+    assert!(root.join("node_modules/left-pad").exists());
+
+    let backups = run_cachekill_in(root, &["backups", "--json"]);
+    assert_eq!(backups["backups"], serde_json::json!([]));
+
+    // Arguments are validated per command
+    assert!(!cachekill(&["ci", "nightly"]).status.success());
+    assert!(!cachekill(&["list", "--keep-versions", "2"])
+        .status
+        .success());
+    assert!(!cachekill(&["list", "--npx", "--docker"]).status.success());
+}
+
+#[test]
+fn test_free_target_plan_stops_when_met() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/big")).unwrap();
+    std::fs::write(root.join("node_modules/big/blob"), vec![0u8; 2_000_000]).unwrap();
+
+    let plan = run_cachekill_in(
+        root,
+        &["--json", "plan", "--free", "1MB", "--safe-delete", "false"],
+    );
+    assert_eq!(plan["mode"], "free-target");
+    assert_eq!(plan["needed_bytes"], 1_000_000);
+    assert_eq!(plan["met"], true);
+    assert_eq!(plan["steps"].as_array().unwrap().len(), 1);
+    assert!(root.join("node_modules/big").exists());
+
+    // Moving to a backup on the same filesystem frees nothing
+    let plan = run_cachekill_in(root, &["--json", "plan", "--free", "1MB"]);
+    assert_eq!(plan["met"], false);
+    assert_eq!(plan["backups_ignored"], 1);
+}
+
+/// Run cachekill in `dir` with `home` as the only home directory, so global caches are
+/// just the ones the test creates
+fn run_cachekill_with_home(dir: &Path, home: &Path, args: &[&str]) -> serde_json::Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args(args)
+        .current_dir(dir)
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .env("HOME", home)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_cache_budget_evicts_least_recently_used() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let home = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/big")).unwrap();
+    std::fs::write(root.join("node_modules/big/blob"), vec![0u8; 2_000_000]).unwrap();
+    std::fs::write(
+        root.join(".cachekillrc"),
+        "max_total_cache = \"1MB\"\nsafe_delete = false\n",
+    )
+    .unwrap();
+
+    let plan = run_cachekill_with_home(root, home.path(), &["--json", "plan"]);
+    assert_eq!(plan["to_delete"].as_array().unwrap().len(), 1);
+
+    std::fs::write(root.join(".cachekillrc"), "max_total_cache = \"1GB\"\n").unwrap();
+    let plan = run_cachekill_with_home(root, home.path(), &["--json", "plan"]);
+    assert_eq!(plan["to_delete"].as_array().unwrap().len(), 0);
+    assert_eq!(
+        plan["to_skip"][0]["skip_reason"],
+        "fits in max_total_cache (1 GB)"
+    );
+}
+
+#[test]
+fn test_cache_budget_counts_global_caches() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let home = tempfile::TempDir::new().unwrap();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/big")).unwrap();
+    std::fs::write(root.join("node_modules/big/blob"), vec![0u8; 2_000_000]).unwrap();
+
+    // A model nobody loaded for a year, outside the project and without --lang ml
+    let hub = home.path().join(".cache/huggingface/hub");
+    std::fs::create_dir_all(&hub).unwrap();
+    std::fs::write(hub.join("model.bin"), vec![0u8; 2_000_000]).unwrap();
+    let year_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(365 * 86400);
+    for path in [
+        hub.join("model.bin"),
+        hub.clone(),
+        hub.parent().unwrap().to_path_buf(),
+    ] {
+        std::fs::File::open(&path)
+            .unwrap()
+            .set_modified(year_ago)
+            .unwrap();
+    }
+    std::fs::write(
+        root.join(".cachekillrc"),
+        "max_total_cache = \"3MB\"\nsafe_delete = false\n",
+    )
+    .unwrap();
+
+    let plan = run_cachekill_with_home(root, home.path(), &["--json", "plan"]);
+    let to_delete = plan["to_delete"].as_array().unwrap();
+    assert_eq!(to_delete.len(), 1);
+    assert_eq!(
+        to_delete[0]["path"],
+        home.path()
+            .join(".cache/huggingface")
+            .to_string_lossy()
+            .as_ref()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_clean_rehydrates_with_regenerate_command() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path().join("app");
+    std::fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::write(root.join("package-lock.json"), "{}").unwrap();
+    std::fs::write(root.join("node_modules/dep/index.js"), "").unwrap();
+
+    // A fake npm records its arguments where node_modules used to be
+    let bin = temp_dir.path().join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let npm = bin.join("npm");
+    std::fs::write(
+        &npm,
+        "#!/bin/sh\nmkdir -p node_modules && echo \"$@\" > node_modules/.rehydrated\necho fake npm $@\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&npm, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args(["--force", "clean", "--safe-delete", "false", "--rehydrate"])
+        .current_dir(&root)
+        .env("PATH", path)
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("Restored 1 of 1 projects"), "{}", stdout);
+    assert!(!root.join("node_modules/dep").exists());
+    assert_eq!(
+        std::fs::read_to_string(root.join("node_modules/.rehydrated")).unwrap(),
+        "ci\n"
+    );
+
+    let log = std::fs::read_dir(root.join(".cachekill-backup"))
+        .unwrap()
+        .flatten()
+        .find(|e| e.file_name().to_string_lossy().starts_with("rehydrate-"))
+        .expect("rehydrate log");
+    assert!(std::fs::read_to_string(log.path())
+        .unwrap()
+        .contains("fake npm ci"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_caches_in_use_are_skipped_unless_holders_are_killed() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/.bin")).unwrap();
+
+    // Stands in for a dev server running inside the cache
+    let mut holder = Command::new("sleep")
+        .arg("30")
+        .current_dir(root.join("node_modules/.bin"))
+        .spawn()
+        .unwrap();
+
+    let plan = run_cachekill_in(root, &["--json", "plan", "--safe-delete", "false"]);
+    assert_eq!(plan["to_delete"].as_array().unwrap().len(), 0);
+    assert_eq!(
+        plan["to_skip"][0]["skip_reason"],
+        format!("in use by pid {} (sleep)", holder.id())
+    );
+
+    // A dry run with --kill-holders plans to stop the holder and clean the cache
+    let plan = run_cachekill_in(
+        root,
+        &["--json", "plan", "--safe-delete", "false", "--kill-holders"],
+    );
+    assert_eq!(plan["to_delete"].as_array().unwrap().len(), 1);
+
+    // A held cache cannot meet a free-space target, so the next candidate is cleaned
+    std::fs::write(root.join("node_modules/.bin/dep"), vec![0u8; 64 * 1024]).unwrap();
+    std::fs::create_dir_all(root.join(".next")).unwrap();
+    std::fs::write(root.join(".next/page"), vec![0u8; 16 * 1024]).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args([
+            "--force",
+            "clean",
+            "--safe-delete",
+            "false",
+            "--free",
+            "8KB",
+        ])
+        .current_dir(root)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(root.join("node_modules").exists());
+    assert!(!root.join(".next").exists());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args([
+            "--force",
+            "clean",
+            "--safe-delete",
+            "false",
+            "--free",
+            "1KB",
+        ])
+        .current_dir(root)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(root.join("node_modules").exists());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args([
+            "--force",
+            "clean",
+            "--safe-delete",
+            "false",
+            "--kill-holders",
+        ])
+        .current_dir(root)
+        .output()
+        .expect("Failed to execute command");
+    let _ = holder.kill();
+    let status = holder.wait().unwrap();
+    assert!(output.status.success());
+    assert!(!status.success());
+    assert!(!root.join("node_modules").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_run_lock_blocks_overlapping_cleanups() {
+    // util-linux flock holds the lock the way another run would
+    if Command::new("flock").arg("--version").output().is_err() {
+        return;
+    }
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+    // The lock is per user, not per project, so it lives outside `root`
+    let state = tempfile::TempDir::new().unwrap();
+    let lock = state.path().join("cachekill/run.lock");
+    std::fs::create_dir_all(lock.parent().unwrap()).unwrap();
+    std::fs::write(&lock, format!("{}\n", std::process::id())).unwrap();
+    let mut holder = Command::new("flock")
+        .arg("--close")
+        .arg(&lock)
+        .args(["sleep", "30"])
+        .spawn()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+
+    let cachekill = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cachekill"))
+            .args(args)
+            .current_dir(root)
+            .env("XDG_STATE_HOME", state.path())
+            .output()
+            .expect("Failed to execute command")
+    };
+    let output = cachekill(&["--no-wait", "--force", "clean", "--safe-delete", "false"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("another cachekill run"));
+    assert!(root.join("node_modules/dep").exists());
+
+    // Planning changes nothing, so it does not need the lock
+    assert!(cachekill(&["--no-wait", "plan"]).status.success());
+
+    let waiter = Command::new(env!("CARGO_BIN_EXE_cachekill"))
+        .args(["--force", "clean", "--safe-delete", "false"])
+        .current_dir(root)
+        .env("XDG_STATE_HOME", state.path())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    assert!(root.join("node_modules/dep").exists());
+    let _ = holder.kill();
+    let _ = holder.wait();
+    let output = waiter.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Waiting for another cachekill run"));
+    assert!(!root.join("node_modules/dep").exists());
+}
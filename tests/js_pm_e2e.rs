@@ -8,6 +8,25 @@ fn manifest_path() -> String {
     p.to_string_lossy().to_string()
 }
 
+const PM_ENV_VARS: &[&str] = &[
+    "npm_config_cache",
+    "NPM_CONFIG_CACHE",
+    "npm_config_userconfig",
+    "NPM_CONFIG_USERCONFIG",
+    "npm_config_store_dir",
+    "NPM_CONFIG_STORE_DIR",
+    "npm_config_cache_dir",
+    "NPM_CONFIG_CACHE_DIR",
+    "PNPM_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+    "YARN_CACHE_FOLDER",
+    "YARN_GLOBAL_FOLDER",
+    "BUN_INSTALL",
+    "BUN_INSTALL_CACHE_DIR",
+    "DENO_DIR",
+];
+
 fn run_with_env_and_cwd(
     cwd: &std::path::Path,
     envs: &[(&str, &std::path::Path)],
@@ -24,6 +43,10 @@ fn run_with_env_and_cwd(
         cmd.arg(a);
     }
     cmd.current_dir(cwd);
+    // Keep the developer's own package manager configuration out of the run
+    for k in PM_ENV_VARS {
+        cmd.env_remove(k);
+    }
     for (k, v) in envs {
         cmd.env(k, v);
     }
@@ -273,3 +296,76 @@ fn js_pm_end_to_end_linux() {
         }
     }
 }
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn js_pm_honors_package_manager_config_linux() {
+    let td = TempDir::new().unwrap();
+    let home = td.path();
+    let npm_cache = home.join("custom-npm");
+    fs::create_dir_all(&npm_cache).unwrap();
+    fs::create_dir_all(home.join("pnpm-home").join("store").join("v3")).unwrap();
+    fs::create_dir_all(home.join("xdg-cache").join("yarn")).unwrap();
+
+    // Project config: relative paths resolve against the rc file's directory
+    let proj = TempDir::new().unwrap();
+    fs::create_dir_all(proj.path().join("yarn-cache")).unwrap();
+    fs::write(
+        proj.path().join(".yarnrc.yml"),
+        "cacheFolder: ./yarn-cache\n",
+    )
+    .unwrap();
+
+    let (ok, out, err) = run_with_env_and_cwd(
+        proj.path(),
+        &[
+            ("HOME", home),
+            ("npm_config_cache", &npm_cache),
+            ("PNPM_HOME", &home.join("pnpm-home")),
+            ("XDG_CACHE_HOME", &home.join("xdg-cache")),
+        ],
+        &["--list", "--json", "--js-pm"],
+    );
+    assert!(ok, "command failed. stderr=\n{}\nstdout=\n{}", err, out);
+
+    for needle in [
+        "custom-npm",
+        "env npm_config_cache",
+        "pnpm-home/store/v3",
+        "$PNPM_HOME/store",
+        "xdg-cache/yarn",
+        "$XDG_CACHE_HOME/yarn",
+        "yarn-cache",
+        ".yarnrc.yml (cacheFolder)",
+    ] {
+        assert!(out.contains(needle), "missing '{}'. out=\n{}", needle, out);
+    }
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn js_pm_lists_bun_and_deno_caches_linux() {
+    let td = TempDir::new().unwrap();
+    let home = td.path();
+    fs::create_dir_all(home.join(".bun").join("install").join("cache")).unwrap();
+    let deno_dir = home.join("deno");
+    fs::create_dir_all(deno_dir.join("deps")).unwrap();
+    fs::create_dir_all(deno_dir.join("gen").join("https").join("deno.land")).unwrap();
+
+    let proj = TempDir::new().unwrap();
+    let (ok, out, err) = run_with_env_and_cwd(
+        proj.path(),
+        &[("HOME", home), ("DENO_DIR", &deno_dir)],
+        &["--list", "--json", "--js-pm"],
+    );
+    assert!(ok, "command failed. stderr=\n{}\nstdout=\n{}", err, out);
+
+    for needle in [
+        ".bun/install/cache",
+        "deno/deps",
+        "deno/gen/https/deno.land",
+        "env DENO_DIR",
+    ] {
+        assert!(out.contains(needle), "missing '{}'. out=\n{}", needle, out);
+    }
+}